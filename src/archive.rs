@@ -0,0 +1,403 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::{
+    Attachment, Cache, ChannelId, GuildId, Message, MessageId, StickerId, UserId,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::config;
+
+/// メッセージの保存先 (UTC の日付ごとに `<YYYY-MM-DD>.jsonl`)
+pub const ARCHIVE_DIR: &str = "data/archive";
+/// ID で素早く引けるよう、メモリに残す直近のメッセージ数
+const RECENT_LIMIT: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchivedAttachment {
+    pub filename: String,
+    pub url: String,
+    pub size: u32,
+    pub content_type: Option<String>,
+}
+
+impl From<&Attachment> for ArchivedAttachment {
+    fn from(attachment: &Attachment) -> Self {
+        Self {
+            filename: attachment.filename.clone(),
+            url: attachment.url.clone(),
+            size: attachment.size,
+            content_type: attachment.content_type.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchivedSticker {
+    pub id: StickerId,
+    pub name: String,
+}
+
+/// 保存したメッセージ
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchivedMessage {
+    pub id: MessageId,
+    pub guild_id: Option<GuildId>,
+    pub guild_name: Option<String>,
+    pub channel_id: ChannelId,
+    pub channel_name: Option<String>,
+    pub author_id: UserId,
+    pub author_name: String,
+    pub author_display_name: String,
+    pub content: String,
+    pub attachments: Vec<ArchivedAttachment>,
+    pub stickers: Vec<ArchivedSticker>,
+    /// 返信先のメッセージ
+    pub reply_to: Option<MessageId>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ArchivedMessage {
+    /// キャッシュから分かる範囲でギルド名・チャンネル名を補って記録を作る
+    pub fn from_message(cache: &Cache, msg: &Message) -> Self {
+        let (guild_name, channel_name) = match msg.guild_id.and_then(|id| cache.guild(id)) {
+            Some(guild) => (
+                Some(guild.name.clone()),
+                guild
+                    .channels
+                    .get(&msg.channel_id)
+                    .map(|channel| channel.name.clone()),
+            ),
+            None => (None, None),
+        };
+        Self {
+            id: msg.id,
+            guild_id: msg.guild_id,
+            guild_name,
+            channel_id: msg.channel_id,
+            channel_name,
+            author_id: msg.author.id,
+            author_name: msg.author.name.clone(),
+            author_display_name: msg.author.display_name().to_string(),
+            content: msg.content.clone(),
+            attachments: msg
+                .attachments
+                .iter()
+                .map(ArchivedAttachment::from)
+                .collect(),
+            stickers: msg
+                .sticker_items
+                .iter()
+                .map(|sticker| ArchivedSticker {
+                    id: sticker.id,
+                    name: sticker.name.clone(),
+                })
+                .collect(),
+            reply_to: msg
+                .message_reference
+                .as_ref()
+                .and_then(|reference| reference.message_id),
+            timestamp: *msg.timestamp,
+        }
+    }
+
+    /// 一覧表示用の 1 行 (`日時 #チャンネル 名前: 本文 [添付: ...]`)
+    pub fn summary(&self) -> String {
+        let channel = self
+            .channel_name
+            .clone()
+            .unwrap_or_else(|| self.channel_id.to_string());
+        let mut line = format!(
+            "{} #{} {}: {}",
+            self.timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            channel,
+            self.author_display_name,
+            self.content
+        );
+        if !self.attachments.is_empty() {
+            let names: Vec<&str> = self
+                .attachments
+                .iter()
+                .map(|attachment| attachment.filename.as_str())
+                .collect();
+            line.push_str(&format!(" [添付: {}]", names.join(", ")));
+        }
+        if !self.stickers.is_empty() {
+            let names: Vec<&str> = self
+                .stickers
+                .iter()
+                .map(|sticker| sticker.name.as_str())
+                .collect();
+            line.push_str(&format!(" [スタンプ: {}]", names.join(", ")));
+        }
+        line
+    }
+}
+
+/// 検索条件 (None の条件は絞り込まない)
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveQuery {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: Option<ChannelId>,
+    pub author_id: Option<UserId>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// 本文に含まれる文字列 (大文字小文字は区別しない)
+    pub contains: Option<String>,
+    /// 返す最大件数 (0 なら無制限)
+    pub limit: usize,
+}
+
+impl ArchiveQuery {
+    fn matches(&self, message: &ArchivedMessage) -> bool {
+        self.guild_id.is_none_or(|id| message.guild_id == Some(id))
+            && self.channel_id.is_none_or(|id| message.channel_id == id)
+            && self.author_id.is_none_or(|id| message.author_id == id)
+            && self.since.is_none_or(|since| message.timestamp >= since)
+            && self.until.is_none_or(|until| message.timestamp < until)
+            && self.contains.as_ref().is_none_or(|text| {
+                message
+                    .content
+                    .to_lowercase()
+                    .contains(&text.to_lowercase())
+            })
+    }
+
+    /// その日のファイルに該当するメッセージがありうるか
+    fn covers(&self, date: NaiveDate) -> bool {
+        self.since.is_none_or(|since| date >= since.date_naive())
+            && self.until.is_none_or(|until| date <= until.date_naive())
+    }
+}
+
+/// メッセージを JSONL で保存し、条件で検索できるようにする
+///
+/// GUI やコマンドなどから共通して使う。
+pub struct MessageArchive {
+    dir: PathBuf,
+    /// 書き込みを直列化するためのロック
+    write_lock: Mutex<()>,
+    recent: Mutex<LruCache<MessageId, ArchivedMessage>>,
+}
+
+impl MessageArchive {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            write_lock: Mutex::new(()),
+            recent: Mutex::new(LruCache::new(NonZeroUsize::new(RECENT_LIMIT).unwrap())),
+        }
+    }
+
+    fn path_for(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", date.format("%Y-%m-%d")))
+    }
+
+    /// メッセージを保存する
+    ///
+    /// 新しい日のファイルを作ったときは、保存期間を過ぎたファイルを削除する。
+    pub fn append(&self, message: ArchivedMessage) -> std::io::Result<()> {
+        let path = self.path_for(message.timestamp.date_naive());
+        let line = serde_json::to_string(&message)?;
+        let new_file = {
+            let _guard = self.write_lock.lock().unwrap();
+            create_dir_all(&self.dir)?;
+            let new_file = !path.exists();
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", line)?;
+            new_file
+        };
+        self.recent.lock().unwrap().put(message.id, message);
+        if new_file {
+            self.remove_expired(config::global().archive.retention_days);
+        }
+        Ok(())
+    }
+
+    /// `retention_days` 日より前のファイルを削除する (0 なら何もしない)
+    pub fn remove_expired(&self, retention_days: u64) {
+        if retention_days == 0 {
+            return;
+        }
+        let oldest = Utc::now().date_naive() - chrono::Days::new(retention_days);
+        for date in self.dates().into_iter().filter(|date| *date < oldest) {
+            let path = self.path_for(date);
+            if let Err(err) = std::fs::remove_file(&path) {
+                tracing::warn!("古いメッセージの削除に失敗しました ({:?}): {:?}", path, err);
+            }
+        }
+    }
+
+    fn read_file(path: &PathBuf) -> Vec<ArchivedMessage> {
+        let Ok(file) = File::open(path) else {
+            return Vec::new();
+        };
+        BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect()
+    }
+
+    /// 保存されている日付 (新しい順)
+    fn dates(&self) -> Vec<NaiveDate> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut dates: Vec<NaiveDate> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                NaiveDate::parse_from_str(name.strip_suffix(".jsonl")?, "%Y-%m-%d").ok()
+            })
+            .collect();
+        dates.sort_unstable_by(|a, b| b.cmp(a));
+        dates
+    }
+
+    /// ID でメッセージを探す
+    ///
+    /// 同じ ID の記録が複数あれば (編集後の内容など) 最後に保存したものを返す。
+    pub fn get(&self, message_id: MessageId) -> Option<ArchivedMessage> {
+        if let Some(message) = self.recent.lock().unwrap().get(&message_id) {
+            return Some(message.clone());
+        }
+        // ID に含まれる作成日時から、保存されている日のファイルを読む
+        let date = message_id.created_at().date_naive();
+        Self::read_file(&self.path_for(date))
+            .into_iter()
+            .rev()
+            .find(|message| message.id == message_id)
+    }
+
+    /// 条件に合うメッセージを新しい順に返す
+    ///
+    /// 編集されたメッセージは最後に保存した内容だけを対象にする。
+    pub fn query(&self, query: &ArchiveQuery) -> Vec<ArchivedMessage> {
+        let mut results = Vec::new();
+        // 編集後の記録は元と同じ日のファイルに追記されるため、後ろから読めば最新の内容が先に来る
+        let mut seen = HashSet::new();
+        for date in self.dates() {
+            if !query.covers(date) {
+                continue;
+            }
+            let messages = Self::read_file(&self.path_for(date));
+            for message in messages.into_iter().rev() {
+                if !seen.insert(message.id) {
+                    continue;
+                }
+                if query.matches(&message) {
+                    results.push(message);
+                    if query.limit > 0 && results.len() >= query.limit {
+                        return results;
+                    }
+                }
+            }
+        }
+        results
+    }
+}
+
+static ARCHIVE: Lazy<MessageArchive> = Lazy::new(|| MessageArchive::new(ARCHIVE_DIR));
+
+pub fn archive() -> &'static MessageArchive {
+    &ARCHIVE
+}
+
+/// ファイルへの書き込みでランタイムを止めないよう、別スレッドでメッセージを保存する
+pub async fn save(message: ArchivedMessage) -> std::io::Result<()> {
+    tokio::task::spawn_blocking(move || archive().append(message))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn message(content: &str, timestamp: DateTime<Utc>) -> ArchivedMessage {
+        ArchivedMessage {
+            id: MessageId::new(1),
+            guild_id: Some(GuildId::new(10)),
+            guild_name: None,
+            channel_id: ChannelId::new(20),
+            channel_name: None,
+            author_id: UserId::new(30),
+            author_name: "alice".to_string(),
+            author_display_name: "Alice".to_string(),
+            content: content.to_string(),
+            attachments: Vec::new(),
+            stickers: Vec::new(),
+            reply_to: None,
+            timestamp,
+        }
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert!(ArchiveQuery::default().matches(&message("hello", at(1, 0))));
+    }
+
+    #[test]
+    fn ids_and_text_are_filtered() {
+        let message = message("Hello World", at(1, 0));
+        let query = ArchiveQuery {
+            guild_id: Some(GuildId::new(10)),
+            channel_id: Some(ChannelId::new(20)),
+            author_id: Some(UserId::new(30)),
+            contains: Some("WORLD".to_string()),
+            ..Default::default()
+        };
+        assert!(query.matches(&message));
+        assert!(!ArchiveQuery {
+            channel_id: Some(ChannelId::new(21)),
+            ..query.clone()
+        }
+        .matches(&message));
+        assert!(!ArchiveQuery {
+            contains: Some("bye".to_string()),
+            ..query
+        }
+        .matches(&message));
+    }
+
+    #[test]
+    fn period_includes_since_and_excludes_until() {
+        let query = ArchiveQuery {
+            since: Some(at(2, 12)),
+            until: Some(at(3, 12)),
+            ..Default::default()
+        };
+        assert!(!query.matches(&message("", at(2, 11))));
+        assert!(query.matches(&message("", at(2, 12))));
+        assert!(!query.matches(&message("", at(3, 12))));
+    }
+
+    #[test]
+    fn covers_only_days_within_period() {
+        let query = ArchiveQuery {
+            since: Some(at(2, 12)),
+            until: Some(at(3, 12)),
+            ..Default::default()
+        };
+        let day = |day| NaiveDate::from_ymd_opt(2025, 6, day).unwrap();
+        assert!(!query.covers(day(1)));
+        assert!(query.covers(day(2)));
+        assert!(query.covers(day(3)));
+        assert!(!query.covers(day(4)));
+        assert!(ArchiveQuery::default().covers(day(1)));
+    }
+}
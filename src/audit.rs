@@ -0,0 +1,513 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{
+    async_trait, ChannelId, Color, Context, CreateAllowedMentions, CreateEmbed, CreateMessage,
+    EventHandler, GuildId, GuildMemberUpdateEvent, Member, Mentionable, Message, MessageId,
+    MessageUpdateEvent, RoleId, User, UserId,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::archive::{self, ArchivedAttachment, ArchivedMessage};
+use crate::guild_settings::GuildSettingsStore;
+use crate::logging::append_log;
+
+/// 監査イベントの記録先 (1 行 1 イベントの JSON)
+const AUDIT_LOG_PATH: &str = "logs/audit.jsonl";
+/// 埋め込みのフィールドに載せる最大文字数
+const FIELD_LIMIT: usize = 1000;
+/// 埋め込みの説明文に載せる最大文字数
+const DESCRIPTION_LIMIT: usize = 4000;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AuditUser {
+    pub id: UserId,
+    pub name: String,
+}
+
+impl From<&User> for AuditUser {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            name: user.name.clone(),
+        }
+    }
+}
+
+impl From<&ArchivedMessage> for AuditUser {
+    fn from(message: &ArchivedMessage) -> Self {
+        Self {
+            id: message.author_id,
+            name: message.author_name.clone(),
+        }
+    }
+}
+
+/// 削除されたメッセージ (内容が保存されていなければ None)
+#[derive(Serialize, Debug, Clone)]
+pub struct DeletedMessage {
+    pub message_id: MessageId,
+    pub author: Option<AuditUser>,
+    pub content: Option<String>,
+    pub attachments: Vec<ArchivedAttachment>,
+}
+
+impl DeletedMessage {
+    fn new(message_id: MessageId, stored: Option<ArchivedMessage>) -> Self {
+        match stored {
+            Some(message) => Self {
+                message_id,
+                author: Some(AuditUser::from(&message)),
+                content: Some(message.content),
+                attachments: message.attachments,
+            },
+            None => Self {
+                message_id,
+                author: None,
+                content: None,
+                attachments: Vec::new(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditEvent {
+    MessageEdit {
+        channel_id: ChannelId,
+        message_id: MessageId,
+        author: Option<AuditUser>,
+        /// 編集前の本文 (保存されていなければ None)
+        before: Option<String>,
+        after: String,
+    },
+    MessageDelete {
+        channel_id: ChannelId,
+        message: DeletedMessage,
+    },
+    BulkDelete {
+        channel_id: ChannelId,
+        messages: Vec<DeletedMessage>,
+    },
+    MemberJoin {
+        user: AuditUser,
+    },
+    MemberLeave {
+        user: AuditUser,
+    },
+    MemberBan {
+        user: AuditUser,
+    },
+    MemberUnban {
+        user: AuditUser,
+    },
+    RoleChange {
+        user: AuditUser,
+        added: Vec<RoleId>,
+        removed: Vec<RoleId>,
+    },
+}
+
+/// 記録する 1 件分の監査イベント
+#[derive(Serialize, Debug, Clone)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    pub guild_id: GuildId,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// 文字数の上限を超える部分を省略する
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(limit).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn user_label(user: &AuditUser) -> String {
+    format!("{} ({})", user.id.mention(), user.name)
+}
+
+fn content_label(content: Option<&str>) -> String {
+    match content {
+        Some("") => "(本文なし)".to_string(),
+        Some(content) => truncate(content, FIELD_LIMIT),
+        None => "(保存されていません)".to_string(),
+    }
+}
+
+impl AuditEvent {
+    /// 監査チャンネルに送る埋め込み
+    fn embed(&self) -> CreateEmbed {
+        match self {
+            AuditEvent::MessageEdit {
+                channel_id,
+                message_id,
+                author,
+                before,
+                after,
+            } => CreateEmbed::new()
+                .title("メッセージの編集")
+                .color(Color::ORANGE)
+                .description(format!(
+                    "{} の {}",
+                    channel_id.mention(),
+                    message_id.link(*channel_id, None)
+                ))
+                .field(
+                    "投稿者",
+                    author.as_ref().map_or("不明".to_string(), user_label),
+                    false,
+                )
+                .field("編集前", content_label(before.as_deref()), false)
+                .field("編集後", content_label(Some(after)), false),
+            AuditEvent::MessageDelete {
+                channel_id,
+                message,
+            } => {
+                let mut embed = CreateEmbed::new()
+                    .title("メッセージの削除")
+                    .color(Color::RED)
+                    .field(
+                        "投稿者",
+                        message
+                            .author
+                            .as_ref()
+                            .map_or("不明".to_string(), user_label),
+                        true,
+                    )
+                    .field("チャンネル", channel_id.mention().to_string(), true)
+                    .field("本文", content_label(message.content.as_deref()), false);
+                if !message.attachments.is_empty() {
+                    let names: Vec<&str> = message
+                        .attachments
+                        .iter()
+                        .map(|attachment| attachment.filename.as_str())
+                        .collect();
+                    embed = embed.field(
+                        "添付ファイル",
+                        truncate(&names.join("\n"), FIELD_LIMIT),
+                        false,
+                    );
+                }
+                embed
+            }
+            AuditEvent::BulkDelete {
+                channel_id,
+                messages,
+            } => {
+                let mut description = format!("{}\n", channel_id.mention());
+                for message in messages {
+                    let author = message
+                        .author
+                        .as_ref()
+                        .map_or("不明".to_string(), |author| author.name.clone());
+                    let line = format!(
+                        "**{}**: {}\n",
+                        author,
+                        truncate(&content_label(message.content.as_deref()), 100)
+                    );
+                    if description.len() + line.len() > DESCRIPTION_LIMIT {
+                        description.push('…');
+                        break;
+                    }
+                    description.push_str(&line);
+                }
+                CreateEmbed::new()
+                    .title(format!("メッセージの一括削除 ({} 件)", messages.len()))
+                    .color(Color::DARK_RED)
+                    .description(description)
+            }
+            AuditEvent::MemberJoin { user } => CreateEmbed::new()
+                .title("メンバーの参加")
+                .color(Color::DARK_GREEN)
+                .description(user_label(user)),
+            AuditEvent::MemberLeave { user } => CreateEmbed::new()
+                .title("メンバーの退出")
+                .color(Color::LIGHT_GREY)
+                .description(user_label(user)),
+            AuditEvent::MemberBan { user } => CreateEmbed::new()
+                .title("メンバーの BAN")
+                .color(Color::RED)
+                .description(user_label(user)),
+            AuditEvent::MemberUnban { user } => CreateEmbed::new()
+                .title("BAN の解除")
+                .color(Color::DARK_GREEN)
+                .description(user_label(user)),
+            AuditEvent::RoleChange {
+                user,
+                added,
+                removed,
+            } => {
+                let roles = |roles: &[RoleId]| {
+                    if roles.is_empty() {
+                        "なし".to_string()
+                    } else {
+                        roles
+                            .iter()
+                            .map(|role| role.mention().to_string())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    }
+                };
+                CreateEmbed::new()
+                    .title("ロールの変更")
+                    .color(Color::BLUE)
+                    .description(user_label(user))
+                    .field("追加", roles(added), false)
+                    .field("削除", roles(removed), false)
+            }
+        }
+    }
+}
+
+/// メッセージの内容をキャッシュ、無ければ保存済みのメッセージから探す
+///
+/// 編集時のキャッシュは既に編集後の内容になっているため、編集前の内容を探すときは使わないこと。
+async fn stored_message(
+    ctx: &Context,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> Option<ArchivedMessage> {
+    let cached = ctx
+        .cache
+        .message(channel_id, message_id)
+        .map(|message| ArchivedMessage::from_message(&ctx.cache, &message));
+    if cached.is_some() {
+        return cached;
+    }
+    archived_message(message_id).await
+}
+
+/// 保存済みのメッセージから探す
+async fn archived_message(message_id: MessageId) -> Option<ArchivedMessage> {
+    tokio::task::spawn_blocking(move || archive::archive().get(message_id))
+        .await
+        .ok()
+        .flatten()
+}
+
+/// メッセージの編集・削除とメンバーの出入り・BAN・ロール変更を記録するハンドラ
+///
+/// すべてのイベントを `logs/audit.jsonl` に書き出し、
+/// ギルド設定で監査チャンネルが指定されていればそこにも埋め込みで送る。
+pub struct AuditLog {
+    guild_settings: Arc<GuildSettingsStore>,
+}
+
+impl AuditLog {
+    pub fn new(guild_settings: Arc<GuildSettingsStore>) -> Self {
+        Self { guild_settings }
+    }
+
+    fn audit_channel(&self, guild_id: GuildId) -> Option<ChannelId> {
+        self.guild_settings.get(guild_id).audit_channel
+    }
+
+    async fn record(&self, ctx: &Context, guild_id: GuildId, event: AuditEvent) {
+        let record = AuditRecord {
+            timestamp: Utc::now(),
+            guild_id,
+            event,
+        };
+        match serde_json::to_string(&record) {
+            Ok(line) => append_log(AUDIT_LOG_PATH, &line),
+            Err(err) => tracing::warn!("監査ログを書き出せません: {:?}", err),
+        }
+        let Some(channel_id) = self.audit_channel(guild_id) else {
+            return;
+        };
+        let message = CreateMessage::new()
+            .embed(record.event.embed().timestamp(record.timestamp))
+            .allowed_mentions(CreateAllowedMentions::new());
+        if let Err(err) = channel_id.send_message(&ctx.http, message).await {
+            tracing::warn!("監査チャンネルに送信できません ({}): {:?}", channel_id, err);
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for AuditLog {
+    async fn message_update(
+        &self,
+        ctx: Context,
+        old_if_available: Option<Message>,
+        new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        // 本文が変わっていない更新 (埋め込みの展開など) は無視する
+        let Some(after) = event.content.clone() else {
+            return;
+        };
+        let Some(guild_id) = event.guild_id else {
+            return;
+        };
+        if event.author.as_ref().is_some_and(|author| author.bot) {
+            return;
+        }
+        // キャッシュはこの時点で編集後の内容に更新されているため、編集前の内容は
+        // Serenity が渡す更新前のメッセージか、保存済みのメッセージから取る
+        let before = match &old_if_available {
+            Some(old) => Some(ArchivedMessage::from_message(&ctx.cache, old)),
+            None => archived_message(event.id).await,
+        };
+        if before
+            .as_ref()
+            .is_some_and(|message| message.content == after)
+        {
+            return;
+        }
+
+        // 編集後の内容も保存し、次の編集や削除で「直前の内容」として使えるようにする
+        let updated = match &new {
+            Some(msg) => Some(ArchivedMessage::from_message(&ctx.cache, msg)),
+            None => before.clone().map(|mut message| {
+                message.content = after.clone();
+                if let Some(attachments) = &event.attachments {
+                    message.attachments =
+                        attachments.iter().map(ArchivedAttachment::from).collect();
+                }
+                message
+            }),
+        };
+        if let Some(updated) = updated {
+            if let Err(err) = archive::save(updated).await {
+                tracing::warn!("編集後のメッセージの保存に失敗しました: {:?}", err);
+            }
+        }
+
+        let author = event
+            .author
+            .as_ref()
+            .map(AuditUser::from)
+            .or_else(|| before.as_ref().map(AuditUser::from));
+        let event = AuditEvent::MessageEdit {
+            channel_id: event.channel_id,
+            message_id: event.id,
+            author,
+            before: before.map(|message| message.content),
+            after,
+        };
+        self.record(&ctx, guild_id, event).await;
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        let Some(guild_id) = guild_id else {
+            return;
+        };
+        // 監査チャンネル内の削除は記録しない (Bot 自身の投稿の整理で溢れないように)
+        if self.audit_channel(guild_id) == Some(channel_id) {
+            return;
+        }
+        let stored = stored_message(&ctx, channel_id, deleted_message_id).await;
+        let event = AuditEvent::MessageDelete {
+            channel_id,
+            message: DeletedMessage::new(deleted_message_id, stored),
+        };
+        self.record(&ctx, guild_id, event).await;
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        guild_id: Option<GuildId>,
+    ) {
+        let Some(guild_id) = guild_id else {
+            return;
+        };
+        if self.audit_channel(guild_id) == Some(channel_id) {
+            return;
+        }
+        let mut messages = Vec::with_capacity(multiple_deleted_messages_ids.len());
+        for message_id in multiple_deleted_messages_ids {
+            let stored = stored_message(&ctx, channel_id, message_id).await;
+            messages.push(DeletedMessage::new(message_id, stored));
+        }
+        // 古い順に並べる
+        messages.sort_by_key(|message| message.message_id);
+        let event = AuditEvent::BulkDelete {
+            channel_id,
+            messages,
+        };
+        self.record(&ctx, guild_id, event).await;
+    }
+
+    async fn guild_member_addition(&self, ctx: Context, new_member: Member) {
+        let event = AuditEvent::MemberJoin {
+            user: AuditUser::from(&new_member.user),
+        };
+        self.record(&ctx, new_member.guild_id, event).await;
+    }
+
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        _member_data_if_available: Option<Member>,
+    ) {
+        let event = AuditEvent::MemberLeave {
+            user: AuditUser::from(&user),
+        };
+        self.record(&ctx, guild_id, event).await;
+    }
+
+    async fn guild_ban_addition(&self, ctx: Context, guild_id: GuildId, banned_user: User) {
+        let event = AuditEvent::MemberBan {
+            user: AuditUser::from(&banned_user),
+        };
+        self.record(&ctx, guild_id, event).await;
+    }
+
+    async fn guild_ban_removal(&self, ctx: Context, guild_id: GuildId, unbanned_user: User) {
+        let event = AuditEvent::MemberUnban {
+            user: AuditUser::from(&unbanned_user),
+        };
+        self.record(&ctx, guild_id, event).await;
+    }
+
+    async fn guild_member_update(
+        &self,
+        ctx: Context,
+        old_if_available: Option<Member>,
+        _new: Option<Member>,
+        event: GuildMemberUpdateEvent,
+    ) {
+        // 変更前のロールが分からない場合は差分を出せないため記録しない
+        let Some(old) = old_if_available else {
+            return;
+        };
+        let added: Vec<RoleId> = event
+            .roles
+            .iter()
+            .filter(|role| !old.roles.contains(role))
+            .copied()
+            .collect();
+        let removed: Vec<RoleId> = old
+            .roles
+            .iter()
+            .filter(|role| !event.roles.contains(role))
+            .copied()
+            .collect();
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        let audit_event = AuditEvent::RoleChange {
+            user: AuditUser::from(&event.user),
+            added,
+            removed,
+        };
+        self.record(&ctx, event.guild_id, audit_event).await;
+    }
+}
@@ -0,0 +1,264 @@
+use dashmap::DashMap;
+use lru::LruCache;
+use poise::serenity_prelude::{
+    self as serenity, async_trait, ChannelId, Context, CreateAllowedMentions, CreateAttachment,
+    CreateWebhook, EventHandler, ExecuteWebhook, HttpError, Message, MessageId, Webhook,
+};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use crate::guild_settings::GuildSettingsStore;
+use crate::sub_command::translate;
+use crate::translation::{self, Origin, TranslateOptions};
+
+/// ブリッジ用に作成する Webhook の名前
+const WEBHOOK_NAME: &str = "VH1 Bridge";
+/// 返信先を辿るために覚えておくメッセージ数
+const COUNTERPART_LIMIT: usize = 10_000;
+/// 返信先の引用に載せる文字数
+const QUOTE_LENGTH: usize = 80;
+/// 1メッセージに送れる最大文字数 (Discord の制限)
+const MESSAGE_LIMIT: usize = 2000;
+/// Discord の "Unknown Webhook" エラーコード
+const UNKNOWN_WEBHOOK: isize = 10015;
+
+/// ブリッジされたチャンネル間でメッセージを翻訳して転送するハンドラ
+///
+/// 転送には発言者の名前とアイコンを使った Webhook を使う。
+/// Bot や Webhook の発言は転送しないため、転送したメッセージが送り返されることはない。
+pub struct Bridge {
+    guild_settings: Arc<GuildSettingsStore>,
+    webhooks: DashMap<ChannelId, Webhook>,
+    /// メッセージ → ブリッジ先の対応するメッセージ (元と転送先の両方向で登録する)
+    counterparts: Mutex<LruCache<MessageId, (ChannelId, MessageId)>>,
+}
+
+impl Bridge {
+    pub fn new(guild_settings: Arc<GuildSettingsStore>) -> Self {
+        Self {
+            guild_settings,
+            webhooks: DashMap::new(),
+            counterparts: Mutex::new(LruCache::new(NonZeroUsize::new(COUNTERPART_LIMIT).unwrap())),
+        }
+    }
+
+    /// チャンネルの転送用 Webhook (無ければ作成する)
+    async fn webhook(&self, ctx: &Context, channel_id: ChannelId) -> Option<Webhook> {
+        if let Some(webhook) = self.webhooks.get(&channel_id) {
+            return Some(webhook.clone());
+        }
+        let bot_id = ctx.cache.current_user().id;
+        let existing = match channel_id.webhooks(&ctx.http).await {
+            Ok(webhooks) => webhooks.into_iter().find(|webhook| {
+                webhook.name.as_deref() == Some(WEBHOOK_NAME)
+                    && webhook.token.is_some()
+                    && webhook.user.as_ref().map(|user| user.id) == Some(bot_id)
+            }),
+            Err(err) => {
+                tracing::warn!("Webhook の一覧を取得できません ({}): {:?}", channel_id, err);
+                return None;
+            }
+        };
+        let webhook = match existing {
+            Some(webhook) => webhook,
+            None => match channel_id
+                .create_webhook(&ctx.http, CreateWebhook::new(WEBHOOK_NAME))
+                .await
+            {
+                Ok(webhook) => webhook,
+                Err(err) => {
+                    tracing::warn!("Webhook を作成できません ({}): {:?}", channel_id, err);
+                    return None;
+                }
+            },
+        };
+        self.webhooks.insert(channel_id, webhook.clone());
+        Some(webhook)
+    }
+
+    /// 返信先がブリッジ先にもあれば、その引用を作る
+    fn reply_quote(&self, msg: &Message, partner: ChannelId) -> Option<String> {
+        let referenced = msg.referenced_message.as_deref()?;
+        let (channel_id, message_id) = *self.counterparts.lock().unwrap().get(&referenced.id)?;
+        if channel_id != partner {
+            return None;
+        }
+        let guild_id = msg.guild_id?;
+        let mut snippet: String = referenced
+            .content
+            .replace('\n', " ")
+            .chars()
+            .take(QUOTE_LENGTH)
+            .collect();
+        if referenced.content.chars().count() > QUOTE_LENGTH {
+            snippet.push('…');
+        }
+        Some(format!(
+            "> ↪ **{}**: {} ({})\n",
+            referenced.author.display_name(),
+            snippet,
+            message_id.link(channel_id, Some(guild_id))
+        ))
+    }
+}
+
+/// 文章を `limit` 文字以下に分割する (できるだけ改行の位置で区切る)
+fn split_message(content: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for line in content.split_inclusive('\n') {
+        let line_len = line.chars().count();
+        if current_len + line_len > limit && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if line_len <= limit {
+            current.push_str(line);
+            current_len += line_len;
+            continue;
+        }
+        // 1行だけで上限を超える場合は文字数で区切る
+        let chars: Vec<char> = line.chars().collect();
+        for part in chars.chunks(limit) {
+            if current_len + part.len() > limit {
+                chunks.push(std::mem::take(&mut current));
+                current_len = 0;
+            }
+            current.extend(part);
+            current_len += part.len();
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Webhook が削除されている (作り直しが必要な) エラーか
+fn is_unknown_webhook(err: &serenity::Error) -> bool {
+    matches!(
+        err,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if response.status_code.as_u16() == 404 || response.error.code == UNKNOWN_WEBHOOK
+    )
+}
+
+/// Webhook の名前に使う表示名 (Discord の制限で80文字まで)
+fn webhook_username(msg: &Message) -> String {
+    let name = msg
+        .member
+        .as_ref()
+        .and_then(|member| member.nick.clone())
+        .unwrap_or_else(|| msg.author.display_name().to_string());
+    name.chars().take(80).collect()
+}
+
+#[async_trait]
+impl EventHandler for Bridge {
+    async fn message(&self, ctx: Context, msg: Message) {
+        // Bot と Webhook (転送したメッセージを含む) の発言は転送しない
+        if msg.author.bot || msg.webhook_id.is_some() {
+            return;
+        }
+        let Some(guild_id) = msg.guild_id else {
+            return;
+        };
+        let settings = self.guild_settings.get(guild_id);
+        let Some((partner, target_lang)) = settings.bridge_partner(msg.channel_id) else {
+            return;
+        };
+        if msg.content.trim().is_empty() && msg.attachments.is_empty() {
+            return;
+        }
+
+        let mut content = String::new();
+        if let Some(quote) = self.reply_quote(&msg, partner) {
+            content.push_str(&quote);
+        }
+        if !msg.content.trim().is_empty() {
+            let origin = Origin {
+                guild_id: Some(guild_id),
+                channel_id: msg.channel_id,
+            };
+            let glossaries =
+                settings.glossaries_for(target_lang, translation::current_backend().name());
+            let options = TranslateOptions::default();
+            match translate(&msg.content, target_lang, origin, &options, &glossaries).await {
+                Ok((source_lang, text))
+                    if !translation::same_language(&source_lang, target_lang) =>
+                {
+                    content.push_str(&text)
+                }
+                Ok(_) => content.push_str(&msg.content),
+                Err(err) => {
+                    // 翻訳できなくても会話が途切れないよう原文で転送する
+                    tracing::warn!("ブリッジの翻訳に失敗しました: {}", err);
+                    content.push_str(&msg.content);
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        for attachment in &msg.attachments {
+            match CreateAttachment::url(&ctx.http, &attachment.url).await {
+                Ok(file) => files.push(file),
+                Err(err) => {
+                    tracing::warn!(
+                        "添付ファイルを取得できません ({}): {:?}",
+                        attachment.filename,
+                        err
+                    );
+                    content.push('\n');
+                    content.push_str(&attachment.url);
+                }
+            }
+        }
+
+        let Some(webhook) = self.webhook(&ctx, partner).await else {
+            return;
+        };
+        // 長い訳文は複数のメッセージに分けて送り、添付ファイルは最後のメッセージに付ける
+        let mut chunks = split_message(&content, MESSAGE_LIMIT);
+        if chunks.is_empty() {
+            chunks.push(String::new());
+        }
+        let last = chunks.len() - 1;
+        let mut first_mirrored = None;
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let mut builder = ExecuteWebhook::new()
+                .username(webhook_username(&msg))
+                .avatar_url(msg.author.face())
+                // 転送先で改めて通知しない
+                .allowed_mentions(CreateAllowedMentions::new())
+                .content(chunk);
+            if i == last {
+                builder = builder.add_files(std::mem::take(&mut files));
+            }
+            match webhook.execute(&ctx.http, true, builder).await {
+                Ok(Some(mirrored)) => {
+                    first_mirrored.get_or_insert(mirrored.id);
+                    self.counterparts
+                        .lock()
+                        .unwrap()
+                        .put(mirrored.id, (msg.channel_id, msg.id));
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::warn!("ブリッジ先への転送に失敗しました: {:?}", err);
+                    // 削除された Webhook を使い続けないよう、次回は取り直す
+                    if is_unknown_webhook(&err) {
+                        self.webhooks.remove(&partner);
+                    }
+                    break;
+                }
+            }
+        }
+        if let Some(mirrored_id) = first_mirrored {
+            self.counterparts
+                .lock()
+                .unwrap()
+                .put(msg.id, (partner, mirrored_id));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use poise::serenity_prelude::{self as serenity, Color, CreateEmbed};
+use poise::ChoiceParameter;
+use std::collections::BTreeMap;
+
+use crate::config;
+use crate::translation;
+use crate::Context;
+use crate::Error;
+
+/// Bot owner commands.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    subcommands("reload", "commands", "translation_cache"),
+    subcommand_required
+)]
+pub async fn admin(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Reload the settings file without restarting the bot.
+#[poise::command(slash_command, prefix_command, owners_only, ephemeral)]
+pub async fn reload(ctx: Context<'_>) -> Result<(), Error> {
+    let message = config::reload_and_report().unwrap_or_else(|message| message);
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Show translation cache statistics, optionally clearing the cache.
+#[poise::command(slash_command, prefix_command, owners_only, ephemeral)]
+pub async fn translation_cache(
+    ctx: Context<'_>,
+    #[description = "Clear all cached translations"] clear: Option<bool>,
+) -> Result<(), Error> {
+    let cache = translation::cache();
+    if clear.unwrap_or(false) {
+        cache.clear();
+    }
+    let stats = cache.stats();
+    let embed = CreateEmbed::new()
+        .title("翻訳キャッシュ")
+        .color(Color::DARK_BLUE)
+        .field("ヒット", stats.hits.to_string(), true)
+        .field("ミス", stats.misses.to_string(), true)
+        .field("ヒット率", format!("{:.1}%", stats.hit_rate()), true)
+        .field(
+            "保持件数",
+            format!("{}/{}", stats.entries, stats.capacity),
+            true,
+        );
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum CommandAction {
+    #[name = "register"]
+    Register,
+    #[name = "unregister"]
+    Unregister,
+    #[name = "diff"]
+    Diff,
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum CommandScope {
+    #[name = "global"]
+    Global,
+    #[name = "this guild"]
+    Guild,
+    #[name = "dev guilds"]
+    DevGuilds,
+}
+
+/// 登録済みのコマンド (名前 → 説明)
+type CommandSet = BTreeMap<String, String>;
+
+/// Bot が持っているコマンドの一覧
+fn local_commands(ctx: Context<'_>) -> CommandSet {
+    poise::builtins::create_application_commands(&ctx.framework().options().commands)
+        .into_iter()
+        .filter_map(|command| {
+            let value = serde_json::to_value(&command).ok()?;
+            let name = value["name"].as_str()?.to_string();
+            let description = value["description"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            Some((name, description))
+        })
+        .collect()
+}
+
+/// Discord に登録されているコマンドの一覧
+async fn remote_commands(
+    ctx: Context<'_>,
+    guild_id: Option<serenity::GuildId>,
+) -> Result<CommandSet, Error> {
+    let commands = match guild_id {
+        Some(guild_id) => guild_id.get_commands(ctx.http()).await?,
+        None => serenity::Command::get_global_commands(ctx.http()).await?,
+    };
+    Ok(commands
+        .into_iter()
+        .map(|command| (command.name, command.description))
+        .collect())
+}
+
+/// ローカルと登録済みのコマンドの差分を文字列にする
+fn format_diff(local: &CommandSet, remote: &CommandSet) -> String {
+    let mut lines = Vec::new();
+    for (name, description) in local {
+        match remote.get(name) {
+            None => lines.push(format!("+ /{}", name)),
+            Some(remote_description) if remote_description != description => {
+                lines.push(format!("~ /{}", name))
+            }
+            Some(_) => {}
+        }
+    }
+    for name in remote.keys().filter(|name| !local.contains_key(*name)) {
+        lines.push(format!("- /{}", name));
+    }
+    if lines.is_empty() {
+        "差分はありません。".to_string()
+    } else {
+        format!("```diff\n{}\n```", lines.join("\n"))
+    }
+}
+
+/// Register, unregister or diff application commands.
+#[poise::command(slash_command, prefix_command, owners_only, ephemeral)]
+pub async fn commands(
+    ctx: Context<'_>,
+    #[description = "What to do"] action: CommandAction,
+    #[description = "Where to do it"] scope: CommandScope,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    // 対象 (None はグローバル)
+    let targets: Vec<Option<serenity::GuildId>> = match scope {
+        CommandScope::Global => vec![None],
+        CommandScope::Guild => vec![Some(ctx.guild_id().ok_or("Guild ID not found")?)],
+        CommandScope::DevGuilds => config::global()
+            .commands
+            .dev_guilds
+            .iter()
+            .map(|id| Some(serenity::GuildId::new(*id)))
+            .collect(),
+    };
+    if targets.is_empty() {
+        ctx.say("dev_guilds が設定されていません。").await?;
+        return Ok(());
+    }
+
+    let commands = &ctx.framework().options().commands;
+    let mut embed = CreateEmbed::new()
+        .title(format!("/admin commands {}", action.name()))
+        .color(Color::DARK_BLUE);
+    for target in targets {
+        let label = target.map_or("global".to_string(), |id| format!("guild {}", id));
+        let result = match action {
+            CommandAction::Register => {
+                let count = commands.len();
+                match target {
+                    Some(guild_id) => {
+                        poise::builtins::register_in_guild(ctx.http(), commands, guild_id).await?
+                    }
+                    None => poise::builtins::register_globally(ctx.http(), commands).await?,
+                }
+                format!("{}件のコマンドを登録しました。", count)
+            }
+            CommandAction::Unregister => {
+                match target {
+                    Some(guild_id) => {
+                        guild_id.set_commands(ctx.http(), vec![]).await?;
+                    }
+                    None => {
+                        serenity::Command::set_global_commands(ctx.http(), vec![]).await?;
+                    }
+                }
+                "コマンドの登録を解除しました。".to_string()
+            }
+            CommandAction::Diff => {
+                format_diff(&local_commands(ctx), &remote_commands(ctx, target).await?)
+            }
+        };
+        embed = embed.field(label, result, false);
+    }
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use chrono::{Duration, Utc};
+use poise::serenity_prelude::{self as serenity, Color, CreateEmbed};
+
+use crate::archive::{self, ArchiveQuery};
+use crate::error_handler::UserError;
+use crate::Context;
+use crate::Error;
+
+/// 埋め込みの説明文に収める最大文字数
+const DESCRIPTION_LIMIT: usize = 4000;
+/// 埋め込みのフィールドに収める最大文字数
+const FIELD_LIMIT: usize = 900;
+/// 1 件あたりに表示する本文の最大文字数
+const LINE_LIMIT: usize = 200;
+
+/// Search archived messages.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("search", "show"),
+    subcommand_required
+)]
+pub async fn archive(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Search messages saved in this server's archive.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    ephemeral
+)]
+pub async fn search(
+    ctx: Context<'_>,
+    #[description = "Text contained in the message"] contains: Option<String>,
+    #[description = "Message author"] user: Option<serenity::User>,
+    #[description = "Channel"] channel: Option<serenity::GuildChannel>,
+    #[description = "How many days back to search (default 7)"]
+    #[min = 1]
+    #[max = 365]
+    days: Option<u32>,
+    #[description = "Maximum number of results (default 20)"]
+    #[min = 1]
+    #[max = 50]
+    limit: Option<usize>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    ctx.defer_ephemeral().await?;
+
+    let query = ArchiveQuery {
+        guild_id: Some(guild_id),
+        channel_id: channel.map(|channel| channel.id),
+        author_id: user.map(|user| user.id),
+        since: Some(Utc::now() - Duration::days(days.unwrap_or(7).into())),
+        until: None,
+        contains: contains.filter(|text| !text.trim().is_empty()),
+        limit: limit.unwrap_or(20),
+    };
+    // ファイルの読み込みはランタイムを止めないよう別スレッドで行う
+    let results = tokio::task::spawn_blocking(move || archive::archive().query(&query)).await?;
+
+    let mut description = String::new();
+    for message in &results {
+        let mut line: String = message.summary().chars().take(LINE_LIMIT).collect();
+        line.push('\n');
+        if description.len() + line.len() > DESCRIPTION_LIMIT {
+            break;
+        }
+        description.push_str(&line);
+    }
+    if description.is_empty() {
+        description = "該当するメッセージはありません。".to_string();
+    }
+    let embed = CreateEmbed::new()
+        .title(format!("メッセージ検索 ({} 件)", results.len()))
+        .color(Color::DARK_BLUE)
+        .description(description);
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Show an archived message by its ID, including deleted messages.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    ephemeral
+)]
+pub async fn show(
+    ctx: Context<'_>,
+    #[description = "Message ID"] message_id: serenity::MessageId,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let message = tokio::task::spawn_blocking(move || archive::archive().get(message_id))
+        .await?
+        .filter(|message| message.guild_id == Some(guild_id))
+        .ok_or_else(|| {
+            UserError::new(
+                "このメッセージは保存されていません。",
+                "This message is not in the archive.",
+            )
+        })?;
+
+    let mut embed = CreateEmbed::new()
+        .title(&message.author_display_name)
+        .color(Color::DARK_BLUE)
+        .description(if message.content.is_empty() {
+            "(本文なし)".to_string()
+        } else {
+            message.content.clone()
+        })
+        .field("投稿者", format!("<@{}>", message.author_id), true)
+        .field("チャンネル", format!("<#{}>", message.channel_id), true)
+        .timestamp(serenity::Timestamp::from(message.timestamp));
+    if let Some(reply_to) = message.reply_to {
+        embed = embed.field("返信先", reply_to.to_string(), true);
+    }
+    if !message.attachments.is_empty() {
+        // フィールドの上限 (1024 文字) に収まらないものはファイル名だけにし、
+        // それも入らなくなったら残りの件数だけを示す
+        let limit = FIELD_LIMIT - "…ほか 1000 件".len();
+        let mut attachments = String::new();
+        for (i, attachment) in message.attachments.iter().enumerate() {
+            let link = format!("[{}]({})\n", attachment.filename, attachment.url);
+            let name = format!("{}\n", attachment.filename);
+            if attachments.len() + link.len() <= limit {
+                attachments.push_str(&link);
+            } else if attachments.len() + name.len() <= limit {
+                attachments.push_str(&name);
+            } else {
+                attachments.push_str(&format!("…ほか {} 件", message.attachments.len() - i));
+                break;
+            }
+        }
+        embed = embed.field("添付ファイル", attachments, false);
+    }
+    if !message.stickers.is_empty() {
+        let stickers: Vec<&str> = message
+            .stickers
+            .iter()
+            .map(|sticker| sticker.name.as_str())
+            .collect();
+        embed = embed.field("スタンプ", stickers.join(", "), false);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
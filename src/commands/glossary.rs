@@ -0,0 +1,316 @@
+use poise::serenity_prelude::{self as serenity, Color, CreateEmbed};
+use std::collections::BTreeMap;
+
+use crate::error_handler::UserError;
+use crate::guild_settings::Glossary;
+use crate::sub_command::{autocomplete_language, resolve_language};
+use crate::translation::{self, TranslateError};
+use crate::Context;
+use crate::Error;
+
+/// 一覧に表示する用語の最大数
+const LIST_LIMIT: usize = 50;
+
+/// 用語集の言語コード (DeepL の用語集は地域なしの言語コードを使う)
+fn glossary_lang(lang: &str) -> String {
+    lang.trim()
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .to_uppercase()
+}
+
+/// 用語や訳語として使えるか (TSV で送るためタブと改行は不可)
+fn validate_entry(term: &str, translation: &str) -> Result<(), Error> {
+    if term.is_empty() || translation.is_empty() {
+        return Err(UserError::new(
+            "用語と訳語は空にできません。",
+            "The term and translation cannot be empty.",
+        )
+        .into());
+    }
+    if [term, translation]
+        .iter()
+        .any(|text| text.contains(['\t', '\n', '\r']))
+    {
+        return Err(UserError::new(
+            "用語と訳語にタブや改行は使えません。",
+            "The term and translation cannot contain tabs or line breaks.",
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// 用語集を編集し、翻訳サービス側の用語集を作り直す
+///
+/// DeepL の用語集は作成後に変更できないため、新しく作ってから古いものを削除する。
+/// `f` がエラーを返した場合は何も変更しない。
+async fn edit_and_sync<F>(
+    ctx: Context<'_>,
+    source: &str,
+    target: &str,
+    f: F,
+) -> Result<Glossary, Error>
+where
+    F: FnOnce(&mut BTreeMap<String, String>) -> Result<(), Error>,
+{
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let supported = translation::target_languages().await;
+    let source_lang = glossary_lang(&resolve_language(&supported, source)?.code);
+    let target_lang = glossary_lang(&resolve_language(&supported, target)?.code);
+    if source_lang == target_lang {
+        return Err(UserError::new(
+            "原文と訳文には異なる言語を指定してください。",
+            "Please choose different source and target languages.",
+        )
+        .into());
+    }
+    ctx.defer_ephemeral().await?;
+
+    let store = &ctx.data().guild_settings;
+    // 通信中に別の編集が割り込むと更新が失われ、作った用語集も孤立するため最後まで保持する
+    let _lock = store.lock(guild_id).await;
+    let mut glossary = store
+        .get(guild_id)
+        .glossaries
+        .into_iter()
+        .find(|g| g.source_lang == source_lang && g.target_lang == target_lang)
+        .unwrap_or_else(|| Glossary {
+            source_lang: source_lang.clone(),
+            target_lang: target_lang.clone(),
+            ..Default::default()
+        });
+    f(&mut glossary.entries)?;
+    let backend = translation::current_backend();
+    // 別の翻訳サービスで作った用語集はそのサービスでは削除できないため、設定から外すだけにする
+    let old_id = glossary
+        .glossary_id
+        .take()
+        .filter(|_| glossary.backend.as_deref() == Some(backend.name()));
+    glossary.backend = None;
+
+    if !glossary.entries.is_empty() {
+        let name = format!("vh1-{}-{}-{}", guild_id, source_lang, target_lang);
+        glossary.glossary_id = backend
+            .create_glossary(&name, &source_lang, &target_lang, &glossary.entries)
+            .await
+            .map_err(TranslateError::into_command_error)?;
+        glossary.backend = glossary
+            .glossary_id
+            .as_ref()
+            .map(|_| backend.name().to_string());
+    }
+
+    store.update(guild_id, |settings| {
+        settings
+            .glossaries
+            .retain(|g| !(g.source_lang == source_lang && g.target_lang == target_lang));
+        if !glossary.entries.is_empty() {
+            settings.glossaries.push(glossary.clone());
+        }
+    })?;
+
+    if let Some(old_id) = old_id {
+        if let Err(err) = backend.delete_glossary(&old_id).await {
+            tracing::warn!("古い用語集 {} を削除できませんでした: {}", old_id, err);
+        }
+    }
+    Ok(glossary)
+}
+
+/// 用語集の内容を埋め込みにまとめる
+fn glossary_embed(glossary: &Glossary) -> CreateEmbed {
+    let mut entries = glossary
+        .entries
+        .iter()
+        .take(LIST_LIMIT)
+        .map(|(term, translation)| format!("`{}` → `{}`", term, translation))
+        .collect::<Vec<_>>();
+    if glossary.entries.len() > LIST_LIMIT {
+        entries.push(format!("…ほか {} 件", glossary.entries.len() - LIST_LIMIT));
+    }
+    CreateEmbed::new()
+        .title(format!(
+            "用語集 {} → {} ({} 件)",
+            glossary.source_lang,
+            glossary.target_lang,
+            glossary.entries.len()
+        ))
+        .color(Color::DARK_BLUE)
+        .description(if entries.is_empty() {
+            "用語がありません".to_string()
+        } else {
+            entries.join("\n")
+        })
+}
+
+async fn reply_glossary(ctx: Context<'_>, glossary: &Glossary) -> Result<(), Error> {
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(glossary_embed(glossary))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Manage translation glossaries for this server.
+///
+/// When a target language has a single glossary, messages translated into it are assumed to be
+/// written in the glossary's source language and are translated in one request. With several
+/// glossaries for the same target, the source language is detected first, so matching messages
+/// are sent twice and use twice the character quota.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("add", "remove", "list", "import"),
+    subcommand_required
+)]
+pub async fn glossary(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Add or update a glossary term.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "Source language (e.g. JA)"]
+    #[autocomplete = "autocomplete_language"]
+    source: String,
+    #[description = "Target language (e.g. EN)"]
+    #[autocomplete = "autocomplete_language"]
+    target: String,
+    #[description = "Term in the source language"] term: String,
+    #[description = "Translation to always use"] translation: String,
+) -> Result<(), Error> {
+    let term = term.trim().to_string();
+    let translation = translation.trim().to_string();
+    validate_entry(&term, &translation)?;
+    let glossary = edit_and_sync(ctx, &source, &target, |entries| {
+        entries.insert(term, translation);
+        Ok(())
+    })
+    .await?;
+    reply_glossary(ctx, &glossary).await
+}
+
+/// Remove a glossary term.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "Source language (e.g. JA)"]
+    #[autocomplete = "autocomplete_language"]
+    source: String,
+    #[description = "Target language (e.g. EN)"]
+    #[autocomplete = "autocomplete_language"]
+    target: String,
+    #[description = "Term to remove"] term: String,
+) -> Result<(), Error> {
+    let term = term.trim().to_string();
+    let glossary = edit_and_sync(ctx, &source, &target, |entries| {
+        // 無い用語のために翻訳サービス側の用語集を作り直さない
+        if entries.remove(&term).is_none() {
+            return Err(UserError::new(
+                format!("用語 `{}` は登録されていません。", term),
+                format!("The term `{}` is not in the glossary.", term),
+            )
+            .into());
+        }
+        Ok(())
+    })
+    .await?;
+    reply_glossary(ctx, &glossary).await
+}
+
+/// List glossary terms.
+#[poise::command(slash_command, prefix_command, guild_only, ephemeral)]
+pub async fn list(
+    ctx: Context<'_>,
+    #[description = "Source language (omit to list all)"] source: Option<String>,
+    #[description = "Target language (omit to list all)"] target: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let source = source.as_deref().map(glossary_lang);
+    let target = target.as_deref().map(glossary_lang);
+    let glossaries: Vec<Glossary> = ctx
+        .data()
+        .guild_settings
+        .get(guild_id)
+        .glossaries
+        .into_iter()
+        .filter(|g| source.as_ref().is_none_or(|lang| &g.source_lang == lang))
+        .filter(|g| target.as_ref().is_none_or(|lang| &g.target_lang == lang))
+        .collect();
+    if glossaries.is_empty() {
+        ctx.say("用語集がありません。").await?;
+        return Ok(());
+    }
+    // 1メッセージに付けられる埋め込みは10個まで
+    let mut reply = poise::CreateReply::default().ephemeral(true);
+    for glossary in glossaries.iter().take(10) {
+        reply = reply.embed(glossary_embed(glossary));
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Import terms from a TSV or CSV file (one "term,translation" per line).
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "Source language (e.g. JA)"]
+    #[autocomplete = "autocomplete_language"]
+    source: String,
+    #[description = "Target language (e.g. EN)"]
+    #[autocomplete = "autocomplete_language"]
+    target: String,
+    #[description = "TSV or CSV file"] file: serenity::Attachment,
+    #[description = "Replace the existing terms instead of merging"] replace: Option<bool>,
+) -> Result<(), Error> {
+    let content = String::from_utf8(file.download().await?).map_err(|_| {
+        UserError::new(
+            "ファイルは UTF-8 で保存してください。",
+            "Please save the file as UTF-8.",
+        )
+    })?;
+    let mut imported = BTreeMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((term, translation)) = line.split_once('\t').or_else(|| line.split_once(','))
+        else {
+            return Err(UserError::new(
+                format!(
+                    "{} 行目に区切り文字 (タブまたはカンマ) がありません。",
+                    i + 1
+                ),
+                format!("Line {} has no separator (tab or comma).", i + 1),
+            )
+            .into());
+        };
+        let (term, translation) = (term.trim(), translation.trim());
+        validate_entry(term, translation)?;
+        imported.insert(term.to_string(), translation.to_string());
+    }
+    if imported.is_empty() {
+        return Err(UserError::new(
+            "取り込める用語がありません。",
+            "There are no terms to import.",
+        )
+        .into());
+    }
+    let glossary = edit_and_sync(ctx, &source, &target, |entries| {
+        if replace.unwrap_or(false) {
+            entries.clear();
+        }
+        entries.extend(imported);
+        Ok(())
+    })
+    .await?;
+    reply_glossary(ctx, &glossary).await
+}
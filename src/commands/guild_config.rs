@@ -0,0 +1,352 @@
+use poise::serenity_prelude::{self as serenity, Color, CreateEmbed, Mentionable};
+use poise::ChoiceParameter;
+
+use crate::error_handler::UserError;
+use crate::guild_settings::{
+    Bridge, GuildSettings, Locale, TranslationChannel, TranslationRole, DEFAULT_PREFIX,
+};
+use crate::sub_command::{autocomplete_language, resolve_language, resolve_languages};
+use crate::translation;
+use crate::Context;
+use crate::Error;
+
+/// 設定内容を埋め込みにまとめる
+fn settings_embed(settings: &GuildSettings) -> CreateEmbed {
+    let roles = if settings.translation_roles.is_empty() {
+        "未設定 (Bot 全体の設定を使用)".to_string()
+    } else {
+        settings
+            .translation_roles
+            .iter()
+            .map(|role| format!("{} → `{}`", role.role_id.mention(), role.target_lang))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let channels = if settings.translation_channels.is_empty() {
+        "未設定".to_string()
+    } else {
+        settings
+            .translation_channels
+            .iter()
+            .map(|rule| {
+                let langs = rule
+                    .target_langs
+                    .iter()
+                    .map(|lang| format!("`{}`", lang))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} → {}", rule.channel_id.mention(), langs)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let bridges = if settings.bridges.is_empty() {
+        "未設定".to_string()
+    } else {
+        settings
+            .bridges
+            .iter()
+            .map(|bridge| {
+                format!(
+                    "{} (`{}`) ⇄ {} (`{}`)",
+                    bridge.channel_a.mention(),
+                    bridge.lang_a,
+                    bridge.channel_b.mention(),
+                    bridge.lang_b
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    CreateEmbed::new()
+        .title("サーバー設定")
+        .color(Color::DARK_BLUE)
+        .field("プレフィックス", format!("`{}`", settings.prefix()), true)
+        .field(
+            "DJ ロール",
+            settings
+                .dj_role
+                .map_or("未設定".to_string(), |role| role.mention().to_string()),
+            true,
+        )
+        .field(
+            "デフォルト音量",
+            settings
+                .default_volume
+                .map_or("未設定".to_string(), |volume| volume.to_string()),
+            true,
+        )
+        .field(
+            "通知チャンネル",
+            settings
+                .notification_channel
+                .map_or("未設定".to_string(), |channel| {
+                    channel.mention().to_string()
+                }),
+            true,
+        )
+        .field(
+            "監査ログ",
+            settings
+                .audit_channel
+                .map_or("未設定".to_string(), |channel| {
+                    channel.mention().to_string()
+                }),
+            true,
+        )
+        .field("言語", settings.locale.name(), true)
+        .field("翻訳ロール", roles, false)
+        .field("翻訳チャンネル", channels, false)
+        .field("ブリッジ", bridges, false)
+}
+
+/// 設定を更新して結果を返信する
+async fn update_and_reply<F>(ctx: Context<'_>, f: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut GuildSettings),
+{
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let settings = ctx.data().guild_settings.update(guild_id, f)?;
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(settings_embed(&settings))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Manage the per-server settings.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands(
+        "show",
+        "prefix",
+        "translate_role_add",
+        "translate_role_remove",
+        "translate_channel_add",
+        "translate_channel_remove",
+        "bridge_add",
+        "bridge_remove",
+        "dj_role",
+        "default_volume",
+        "notification_channel",
+        "audit_channel",
+        "locale"
+    ),
+    subcommand_required
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the current server settings.
+#[poise::command(slash_command, prefix_command, guild_only, ephemeral)]
+pub async fn show(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    let settings = ctx.data().guild_settings.get(guild_id);
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(settings_embed(&settings))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Set the prefix for prefix commands (omit to reset).
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn prefix(
+    ctx: Context<'_>,
+    #[description = "New prefix (default: s!)"] prefix: Option<String>,
+) -> Result<(), Error> {
+    let prefix = prefix
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty() && p != DEFAULT_PREFIX);
+    update_and_reply(ctx, |settings| settings.prefix = prefix).await
+}
+
+/// Auto-translate messages from members with a role.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn translate_role_add(
+    ctx: Context<'_>,
+    #[description = "Role whose messages are translated"] role: serenity::Role,
+    #[description = "DeepL target language (e.g. JA, EN-US)"]
+    #[autocomplete = "autocomplete_language"]
+    language: String,
+) -> Result<(), Error> {
+    let supported = translation::target_languages().await;
+    let target_lang = resolve_language(&supported, &language)?.code.clone();
+    update_and_reply(ctx, |settings| {
+        settings
+            .translation_roles
+            .retain(|r| !(r.role_id == role.id && r.target_lang == target_lang));
+        settings.translation_roles.push(TranslationRole {
+            role_id: role.id,
+            target_lang,
+        });
+    })
+    .await
+}
+
+/// Stop auto-translating messages from a role.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn translate_role_remove(
+    ctx: Context<'_>,
+    #[description = "Role to remove"] role: serenity::Role,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| {
+        settings.translation_roles.retain(|r| r.role_id != role.id)
+    })
+    .await
+}
+
+/// Auto-translate every message in a channel.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn translate_channel_add(
+    ctx: Context<'_>,
+    #[description = "Channel whose messages are translated"] channel: serenity::GuildChannel,
+    #[description = "DeepL target languages, comma separated (e.g. EN-US, JA)"]
+    #[autocomplete = "autocomplete_language"]
+    languages: String,
+) -> Result<(), Error> {
+    let supported = translation::target_languages().await;
+    let target_langs: Vec<String> = resolve_languages(&supported, &languages)?
+        .into_iter()
+        .map(|language| language.code.clone())
+        .collect();
+    update_and_reply(ctx, |settings| {
+        settings
+            .translation_channels
+            .retain(|rule| rule.channel_id != channel.id);
+        settings.translation_channels.push(TranslationChannel {
+            channel_id: channel.id,
+            target_langs,
+        });
+    })
+    .await
+}
+
+/// Stop auto-translating a channel.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn translate_channel_remove(
+    ctx: Context<'_>,
+    #[description = "Channel to remove"] channel: serenity::GuildChannel,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| {
+        settings
+            .translation_channels
+            .retain(|rule| rule.channel_id != channel.id)
+    })
+    .await
+}
+
+/// Mirror two channels into each other, translating each way.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn bridge_add(
+    ctx: Context<'_>,
+    #[description = "First channel"] channel_a: serenity::GuildChannel,
+    #[description = "Language of the first channel (e.g. JA)"]
+    #[autocomplete = "autocomplete_language"]
+    language_a: String,
+    #[description = "Second channel"] channel_b: serenity::GuildChannel,
+    #[description = "Language of the second channel (e.g. EN-US)"]
+    #[autocomplete = "autocomplete_language"]
+    language_b: String,
+) -> Result<(), Error> {
+    if channel_a.id == channel_b.id {
+        return Err(UserError::new(
+            "異なるチャンネルを指定してください。",
+            "Please choose two different channels.",
+        )
+        .into());
+    }
+    let supported = translation::target_languages().await;
+    let lang_a = resolve_language(&supported, &language_a)?.code.clone();
+    let lang_b = resolve_language(&supported, &language_b)?.code.clone();
+    update_and_reply(ctx, |settings| {
+        // 1つのチャンネルは1つのブリッジにだけ属する
+        settings.bridges.retain(|bridge| {
+            ![bridge.channel_a, bridge.channel_b]
+                .iter()
+                .any(|id| *id == channel_a.id || *id == channel_b.id)
+        });
+        settings.bridges.push(Bridge {
+            channel_a: channel_a.id,
+            lang_a,
+            channel_b: channel_b.id,
+            lang_b,
+        });
+    })
+    .await
+}
+
+/// Remove the bridge a channel belongs to.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn bridge_remove(
+    ctx: Context<'_>,
+    #[description = "Either channel of the bridge"] channel: serenity::GuildChannel,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| {
+        settings
+            .bridges
+            .retain(|bridge| bridge.channel_a != channel.id && bridge.channel_b != channel.id)
+    })
+    .await
+}
+
+/// Restrict playback controls to a role (omit to allow everyone).
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn dj_role(
+    ctx: Context<'_>,
+    #[description = "DJ role"] role: Option<serenity::Role>,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| settings.dj_role = role.map(|r| r.id)).await
+}
+
+/// Set the volume new players start with (omit to reset).
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn default_volume(
+    ctx: Context<'_>,
+    #[description = "Volume (0-1000)"]
+    #[max = 1000]
+    volume: Option<u16>,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| settings.default_volume = volume).await
+}
+
+/// Send music notifications to a fixed channel (omit to reset).
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn notification_channel(
+    ctx: Context<'_>,
+    #[description = "Notification channel"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| {
+        settings.notification_channel = channel.map(|c| c.id)
+    })
+    .await
+}
+
+/// Post moderation events such as edits, deletions and bans to a channel (omit to disable).
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn audit_channel(
+    ctx: Context<'_>,
+    #[description = "Audit log channel"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| {
+        settings.audit_channel = channel.map(|c| c.id)
+    })
+    .await
+}
+
+/// Set the language the bot replies in.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn locale(
+    ctx: Context<'_>,
+    #[description = "Language"] locale: Locale,
+) -> Result<(), Error> {
+    update_and_reply(ctx, |settings| settings.locale = locale).await
+}
@@ -1,2 +1,7 @@
+pub mod admin;
+pub mod archive;
+pub mod glossary;
+pub mod guild_config;
 pub mod music;
 pub mod test;
+pub mod translate;
@@ -6,7 +6,7 @@ use rand::seq::SliceRandom;
 use std::collections::VecDeque;
 use std::time::Duration;
 
-use crate::commands::music::music_basic::PlayerState;
+use crate::commands::music::music_basic::{dj_check, PlayerState};
 use crate::Context;
 use crate::Error;
 
@@ -18,7 +18,7 @@ async fn get_player_context_from_ctx(ctx: &Context<'_>) -> Option<PlayerContext>
 }
 
 /// Skip the current song.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn skip(ctx: Context<'_>, number: Option<usize>) -> Result<(), Error> {
     if let Some(player) = get_player_context_from_ctx(&ctx).await {
         let now_playing = player.get_player().await?.track;
@@ -47,7 +47,7 @@ pub async fn skip(ctx: Context<'_>, number: Option<usize>) -> Result<(), Error>
 }
 
 /// Pause the current song.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn pause(ctx: Context<'_>) -> Result<(), Error> {
     if let Some(player) = get_player_context_from_ctx(&ctx).await {
         player.set_pause(true).await?;
@@ -64,7 +64,7 @@ pub async fn pause(ctx: Context<'_>) -> Result<(), Error> {
 }
 
 /// Resume playing the current song.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn resume(ctx: Context<'_>) -> Result<(), Error> {
     if let Some(player) = get_player_context_from_ctx(&ctx).await {
         player.set_pause(false).await?;
@@ -81,7 +81,7 @@ pub async fn resume(ctx: Context<'_>) -> Result<(), Error> {
 }
 
 /// Stop the current song.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
     if let Some(player) = get_player_context_from_ctx(&ctx).await {
         let now_playing = player.get_player().await?.track;
@@ -103,7 +103,7 @@ pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
 }
 
 /// Jump to a specific time in the song, in seconds.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn seek(
     ctx: Context<'_>,
     #[description = "Time to jump to (in seconds)"] time: u64,
@@ -127,7 +127,7 @@ pub async fn seek(
 }
 
 /// Remove a specific song from the queue.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn remove(
     ctx: Context<'_>,
     #[description = "Queue item index to remove"] index: usize,
@@ -147,7 +147,7 @@ pub async fn remove(
 }
 
 /// Clear the current queue.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn clear(ctx: Context<'_>) -> Result<(), Error> {
     if let Some(player) = get_player_context_from_ctx(&ctx).await {
         player.get_queue().clear()?;
@@ -164,7 +164,7 @@ pub async fn clear(ctx: Context<'_>) -> Result<(), Error> {
 }
 
 /// Set the volume of the current player.
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn set_volume(ctx: Context<'_>, volume: u16) -> Result<(), Error> {
     if let Some(player) = get_player_context_from_ctx(&ctx).await {
         match player.set_volume(volume).await {
@@ -209,7 +209,7 @@ pub async fn queue(ctx: Context<'_>, n: usize) -> Result<(), Error> {
             .fields(fields);
         let builder = CreateMessage::new().tts(false).embed(embed);
         if let Err(e) = ctx.channel_id().send_message(&ctx.http(), builder).await {
-            println!("Error sending queue message: {}", e);
+            tracing::warn!("Error sending queue message: {}", e);
         }
     } else {
         let embed = CreateEmbed::new()
@@ -223,7 +223,7 @@ pub async fn queue(ctx: Context<'_>, n: usize) -> Result<(), Error> {
     Ok(())
 }
 
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn shuffle(ctx: Context<'_>) -> Result<(), Error> {
     ctx.say("キューをシャッフルしています...").await?;
     if let Some(player) = get_player_context_from_ctx(&ctx).await {
@@ -250,8 +250,7 @@ pub async fn shuffle(ctx: Context<'_>) -> Result<(), Error> {
 
         // 一度クリアしてから、シャッフル後の順番で再度追加
         queue_controller.clear()?;
-        let deque = VecDeque::from(current_queue);
-        queue_controller.append(deque)?;
+        queue_controller.append(current_queue)?;
 
         let embed = CreateEmbed::new()
             .title("Queue Shuffled")
@@ -271,7 +270,7 @@ pub async fn shuffle(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn repeat(ctx: Context<'_>) -> Result<(), Error> {
     ctx.say("リピート設定を変更しています...").await?;
     // プレイヤーコンテキストを取得
@@ -1,3 +1,4 @@
+use crate::error_handler::UserError;
 use crate::Context;
 use crate::Error;
 
@@ -51,7 +52,12 @@ pub async fn _join(
                 .voice_states
                 .get(&ctx.author().id)
                 .and_then(|state| state.channel_id)
-                .ok_or("Not in a voice channel")?
+                .ok_or_else(|| {
+                    UserError::new(
+                        "ボイスチャンネルに参加していません。",
+                        "You are not in a voice channel.",
+                    )
+                })?
         };
 
         // Songbird で接続
@@ -66,7 +72,8 @@ pub async fn _join(
 
         match join_result {
             Ok((connection_info, _)) => {
-                lava_client
+                let guild_settings = ctx.data().guild_settings.get(guild_id);
+                let player = lava_client
                     .create_player_context_with_data::<tokio::sync::Mutex<PlayerState>>(
                         lavalink_guild_id(guild_id),
                         lavalink_rs::model::player::ConnectionInfo {
@@ -76,12 +83,17 @@ pub async fn _join(
                         },
                         Arc::new(tokio::sync::Mutex::new(PlayerState {
                             voice_channel_id: connect_to,
-                            text_channel_id: ctx.channel_id(),
+                            text_channel_id: guild_settings
+                                .notification_channel
+                                .unwrap_or(ctx.channel_id()),
                             http: ctx.serenity_context().http.clone(),
                             repeat: false,
                         })),
                     )
                     .await?;
+                if let Some(volume) = guild_settings.default_volume {
+                    player.set_volume(volume).await?;
+                }
                 ctx.say(format!("Joined {}", connect_to.mention())).await?;
                 Ok(true)
             }
@@ -96,6 +108,35 @@ pub async fn _join(
     }
 }
 
+/// DJ ロールが設定されている場合、ロール所持者とサーバー管理者だけに操作を許可する
+pub async fn dj_check(ctx: Context<'_>) -> Result<bool, Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(true);
+    };
+    let Some(dj_role) = ctx.data().guild_settings.get(guild_id).dj_role else {
+        return Ok(true);
+    };
+    let member = ctx.author_member().await.ok_or("Member not found")?;
+    let allowed = member.roles.contains(&dj_role)
+        || ctx
+            .guild()
+            .is_some_and(|guild| guild.member_permissions(&member).manage_guild());
+    if !allowed {
+        return Err(UserError::new(
+            format!(
+                "このコマンドは {} を持つメンバーのみ使用できます。",
+                dj_role.mention()
+            ),
+            format!(
+                "Only members with {} can use this command.",
+                dj_role.mention()
+            ),
+        )
+        .into());
+    }
+    Ok(true)
+}
+
 /// 曲を再生するコマンド
 #[poise::command(slash_command, prefix_command)]
 pub async fn play(
@@ -214,7 +255,7 @@ pub async fn join(ctx: Context<'_>, channel_id: Option<serenity::ChannelId>) ->
 }
 
 /// ボイスチャンネルから退出するコマンド
-#[poise::command(slash_command, prefix_command)]
+#[poise::command(slash_command, prefix_command, check = "dj_check")]
 pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
     let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
     let manager = songbird::get(ctx.serenity_context())
@@ -225,7 +266,7 @@ pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
 
     // Lavalink プレイヤーの削除
     if let Err(err) = lava_client.delete_player(lavalink_guild_id(guild_id)).await {
-        println!("Error deleting Lavalink player: {}", err);
+        tracing::warn!("Error deleting Lavalink player: {}", err);
     }
 
     // Songbird からの退出
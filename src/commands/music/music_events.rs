@@ -54,7 +54,7 @@ pub async fn track_start(client: LavalinkClient, _session_id: String, event: &ev
     };
 
     if let Err(e) = channel_id.send_message(&http, message).await {
-        println!("Error sending message in track_start hook: {:?}\n\n\n\n", e);
+        tracing::warn!("Error sending message in track_start hook: {:?}", e);
     }
 }
 
@@ -0,0 +1,157 @@
+use poise::serenity_prelude::{self as serenity, Color, CreateEmbed, Mentionable};
+
+use crate::error_handler::UserError;
+use crate::sub_command::{autocomplete_language, resolve_language, translate as translate_text};
+use crate::translation::{self, Origin, TranslateError, TranslateOptions};
+use crate::Context;
+use crate::Error;
+
+/// コマンドを実行したユーザーの翻訳先言語
+///
+/// ユーザー設定があればそれを、無ければ Discord の表示言語を使う。
+fn preferred_lang(ctx: Context<'_>) -> String {
+    ctx.data()
+        .user_settings
+        .get(ctx.author().id)
+        .translate_lang
+        .or_else(|| ctx.locale().map(translation::target_lang_for_locale))
+        .unwrap_or_else(|| "JA".to_string())
+}
+
+/// Translation settings and tools.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("language", "usage"),
+    subcommand_required
+)]
+pub async fn translate(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Set your preferred translation language (omit to follow your Discord language).
+#[poise::command(slash_command, prefix_command, ephemeral)]
+pub async fn language(
+    ctx: Context<'_>,
+    #[description = "DeepL target language (e.g. JA, EN-US)"]
+    #[autocomplete = "autocomplete_language"]
+    language: Option<String>,
+) -> Result<(), Error> {
+    let translate_lang = match language.filter(|lang| !lang.trim().is_empty()) {
+        Some(lang) => {
+            let supported = translation::target_languages().await;
+            Some(resolve_language(&supported, &lang)?.code.clone())
+        }
+        None => None,
+    };
+    ctx.data()
+        .user_settings
+        .update(ctx.author().id, |settings| {
+            settings.translate_lang = translate_lang.clone()
+        })?;
+    let message = match translate_lang {
+        Some(lang) => format!("翻訳先の言語を `{}` に設定しました。", lang),
+        None => format!(
+            "翻訳先の言語を Discord の表示言語 (`{}`) に戻しました。",
+            preferred_lang(ctx)
+        ),
+    };
+    ctx.say(message).await?;
+    Ok(())
+}
+
+/// Show translation usage for this billing period.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    ephemeral
+)]
+pub async fn usage(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Guild ID not found")?;
+    ctx.defer_ephemeral().await?;
+
+    let service = match translation::check_usage(&ctx.serenity_context().http).await {
+        Some(usage) => match usage.percent() {
+            Some(percent) => format!(
+                "{} / {} 文字 ({:.1}%)",
+                usage.character_count, usage.character_limit, percent
+            ),
+            None => format!("{} 文字 (上限なし)", usage.character_count),
+        },
+        None => "取得できません".to_string(),
+    };
+    let tracker = translation::usage_tracker();
+    let channels = tracker.top_channels(Some(guild_id), 10);
+    let channels = if channels.is_empty() {
+        "なし".to_string()
+    } else {
+        channels
+            .iter()
+            .map(|(channel_id, characters)| {
+                format!("{}: {} 文字", channel_id.mention(), characters)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::new()
+        .title("翻訳の使用量")
+        .color(Color::DARK_BLUE)
+        .field(translation::current_backend().name(), service, false)
+        .field(
+            "このサーバー (今月)",
+            format!("{} 文字", tracker.guild_characters(guild_id)),
+            false,
+        )
+        .field("チャンネル別 (今月)", channels, false);
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Translate this message into your language.
+#[poise::command(context_menu_command = "Translate", ephemeral)]
+pub async fn translate_message(
+    ctx: Context<'_>,
+    #[description = "Message to translate"] msg: serenity::Message,
+) -> Result<(), Error> {
+    if msg.content.trim().is_empty() {
+        return Err(UserError::new(
+            "このメッセージには翻訳できる本文がありません。",
+            "This message has no text to translate.",
+        )
+        .into());
+    }
+    let target_lang = preferred_lang(ctx);
+    let origin = Origin {
+        guild_id: ctx.guild_id(),
+        channel_id: ctx.channel_id(),
+    };
+    let guild_settings = ctx
+        .guild_id()
+        .map(|guild_id| ctx.data().guild_settings.get(guild_id))
+        .unwrap_or_default();
+    let glossaries =
+        guild_settings.glossaries_for(&target_lang, translation::current_backend().name());
+    let options = TranslateOptions::default();
+    let (source_lang, text) =
+        translate_text(&msg.content, &target_lang, origin, &options, &glossaries)
+            .await
+            .map_err(TranslateError::into_command_error)?;
+
+    let description = if translation::same_language(&source_lang, &target_lang) {
+        format!("このメッセージは既に `{}` で書かれています。", source_lang)
+    } else {
+        format!("`{}` → `{}`: {}", source_lang, target_lang, text)
+    };
+    let embed = CreateEmbed::new()
+        .title(&msg.author.name)
+        .url(msg.link())
+        .color(Color::DARK_BLUE)
+        .description(description);
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,888 @@
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// 設定ファイルのパスを指定する環境変数
+pub const CONFIG_PATH_ENV: &str = "VH1_CONFIG";
+/// 既定の設定ファイル名
+pub const CONFIG_FILE_NAME: &str = "Setting.toml";
+/// XDG config ディレクトリ配下のアプリ用ディレクトリ名
+const APP_DIR_NAME: &str = "vh1-bot";
+
+// ------------------------------- 設定用構造体 -------------------------------
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Database {
+    pub token: Tokens,
+    pub endpoint: Endpoints,
+    pub id: Id,
+    pub lavalink: LavalinkSettings,
+    pub commands: CommandSettings,
+    pub translation: TranslationSettings,
+    pub logging: LogSettings,
+    pub archive: ArchiveSettings,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Tokens {
+    pub token: String,
+    pub api_key: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Endpoints {
+    pub api_endpoint: String,
+}
+
+/// サーバー設定に翻訳ロールが無いときに使う自動翻訳ロール (0 なら未設定)
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Id {
+    pub translate_ja: u64,
+    pub translate_en: u64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct TranslationSettings {
+    pub backend: BackendKind,
+    /// DeepL の契約プラン (`endpoint.api_endpoint` が空のときの接続先を決める)
+    pub deepl_plan: DeeplPlan,
+    pub libretranslate: LibreTranslateSettings,
+    pub cache: TranslationCacheSettings,
+    pub usage: UsageSettings,
+}
+
+/// 翻訳に使うサービス
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    #[default]
+    Deepl,
+    #[serde(rename = "libretranslate")]
+    LibreTranslate,
+    /// 外部に接続しない動作確認用
+    Mock,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeeplPlan {
+    /// API キーが `:fx` で終われば Free、それ以外は Pro
+    #[default]
+    Auto,
+    Free,
+    Pro,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct LibreTranslateSettings {
+    /// 例: "http://localhost:5000"
+    pub url: String,
+    pub api_key: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TranslationCacheSettings {
+    /// メモリに保持する翻訳結果の件数 (0 でキャッシュ無効)
+    pub capacity: usize,
+    /// true なら翻訳結果を `path` にも保存し、再起動後も使う
+    pub persist: bool,
+    pub path: PathBuf,
+}
+
+impl Default for TranslationCacheSettings {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            persist: false,
+            path: PathBuf::from("data/translation_cache.jsonl"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct UsageSettings {
+    /// 使用量の警告を送るチャンネル ID (0 なら送らない)
+    pub admin_channel: u64,
+    /// 警告する使用率 (%)
+    pub thresholds: Vec<u8>,
+    /// DeepL の使用量を確認する間隔 (秒)
+    pub check_interval_secs: u64,
+}
+
+impl Default for UsageSettings {
+    fn default() -> Self {
+        Self {
+            admin_channel: 0,
+            thresholds: vec![80, 95],
+            check_interval_secs: 600,
+        }
+    }
+}
+
+/// `logs/` に書き出すログファイルのローテーション設定
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LogSettings {
+    /// 日付が変わったら新しいファイルに切り替える
+    pub rotate_daily: bool,
+    /// ファイルがこのサイズ (MB) を超えたら切り替える (0 なら無制限)
+    pub max_size_mb: u64,
+    /// 切り替えた古いファイルを残す日数 (0 なら削除しない)
+    pub retention_days: u64,
+    /// 切り替えた古いファイルを gzip で圧縮する
+    pub compress: bool,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            rotate_daily: true,
+            max_size_mb: 10,
+            retention_days: 14,
+            compress: true,
+        }
+    }
+}
+
+/// `data/archive` に保存するメッセージの設定
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ArchiveSettings {
+    /// メッセージを残す日数 (0 なら削除しない)
+    pub retention_days: u64,
+}
+
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        Self { retention_days: 90 }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct LavalinkSettings {
+    /// `[[lavalink.nodes]]` で指定するノード一覧 (未指定なら localhost:2333 のみ)
+    pub nodes: Vec<LavalinkNode>,
+    pub strategy: NodeStrategy,
+    /// ローカルで起動する Lavalink プロセスの設定
+    pub process: LavalinkProcess,
+}
+
+impl Default for LavalinkSettings {
+    fn default() -> Self {
+        Self {
+            nodes: vec![LavalinkNode::default()],
+            strategy: NodeStrategy::default(),
+            process: LavalinkProcess::default(),
+        }
+    }
+}
+
+impl LavalinkSettings {
+    /// プロセス監視の対象となるノード
+    ///
+    /// `process.node` が未指定なら、最初のローカルホストのノードを使う。
+    pub fn supervised_node(&self) -> Option<&LavalinkNode> {
+        match self.process.node {
+            Some(index) => self.nodes.get(index),
+            None => self.nodes.iter().find(|node| node.is_local()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LavalinkProcess {
+    /// false ならプロセスを起動せず、既に動いているノードに接続する
+    pub enabled: bool,
+    /// java の実行ファイル (PATH 上の名前かフルパス)
+    pub java: PathBuf,
+    /// Lavalink.jar のパス (working_dir からの相対パスも可)
+    pub jar: PathBuf,
+    pub working_dir: PathBuf,
+    /// `-jar` の前に渡す JVM 引数 (例: ["-Xmx1G"])
+    pub jvm_args: Vec<String>,
+    /// ヘルスチェック先のノード番号 (`lavalink.nodes` のインデックス)
+    pub node: Option<usize>,
+    /// `/version` が応答するまで待つ秒数
+    pub ready_timeout_secs: u64,
+    /// 再起動時の待機時間の上限 (秒)
+    pub max_backoff_secs: u64,
+    /// 停止時に終了を待つ秒数 (過ぎたら強制終了)
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for LavalinkProcess {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            java: PathBuf::from("java"),
+            jar: PathBuf::from("Lavalink.jar"),
+            working_dir: PathBuf::from("Lavalink"),
+            jvm_args: Vec::new(),
+            node: None,
+            ready_timeout_secs: 60,
+            max_backoff_secs: 60,
+            shutdown_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LavalinkNode {
+    pub hostname: String,
+    pub port: u16,
+    pub ssl: bool,
+    /// 直接書くこともできるが、`password_env` か `VH1_LAVALINK_PASSWORD` の利用を推奨
+    pub password: String,
+    /// パスワードを読み込む環境変数名
+    pub password_env: Option<String>,
+}
+
+impl Default for LavalinkNode {
+    fn default() -> Self {
+        Self {
+            hostname: "localhost".to_string(),
+            port: 2333,
+            ssl: false,
+            password: String::new(),
+            password_env: None,
+        }
+    }
+}
+
+impl LavalinkNode {
+    /// lavalink-rs の NodeBuilder に渡す `host:port` 形式のアドレス
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.hostname, self.port)
+    }
+
+    /// REST API のベース URL
+    pub fn base_url(&self) -> String {
+        let scheme = if self.ssl { "https" } else { "http" };
+        format!("{}://{}", scheme, self.address())
+    }
+
+    pub fn is_local(&self) -> bool {
+        matches!(
+            self.hostname.as_str(),
+            "localhost" | "127.0.0.1" | "::1" | "[::1]"
+        )
+    }
+}
+
+/// ノードの振り分け方法
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeStrategy {
+    Sharded,
+    #[default]
+    RoundRobin,
+    MainFallback,
+    LowestLoad,
+    HighestFreeMemory,
+}
+
+impl NodeStrategy {
+    pub fn to_distribution(self) -> lavalink_rs::prelude::NodeDistributionStrategy {
+        use lavalink_rs::prelude::NodeDistributionStrategy;
+        match self {
+            NodeStrategy::Sharded => NodeDistributionStrategy::sharded(),
+            NodeStrategy::RoundRobin => NodeDistributionStrategy::round_robin(),
+            NodeStrategy::MainFallback => NodeDistributionStrategy::main_fallback(),
+            NodeStrategy::LowestLoad => NodeDistributionStrategy::lowest_load(),
+            NodeStrategy::HighestFreeMemory => NodeDistributionStrategy::highest_free_memory(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct CommandSettings {
+    /// 起動時にスラッシュコマンドをどこへ登録するか
+    pub register_on_start: RegisterMode,
+    /// 開発用ギルド (`register_on_start = "dev_guilds"` のときの登録先)
+    pub dev_guilds: Vec<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegisterMode {
+    /// 全体に登録する (反映に最大1時間かかる)
+    #[default]
+    Global,
+    /// dev_guilds にだけ登録する (即時反映)
+    DevGuilds,
+    /// 登録しない (`/admin commands` で手動同期する)
+    None,
+}
+
+// ------------------------------- エラー型 -------------------------------
+/// 設定項目ごとの検証エラー
+#[derive(Debug)]
+pub struct FieldError {
+    pub key: String,
+    pub reason: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.key, self.reason)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// 明示的に指定された設定ファイルが存在しない
+    NotFound(PathBuf),
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    /// 必須項目の欠落や不正な値 (まとめて報告する)
+    Invalid {
+        path: Option<PathBuf>,
+        errors: Vec<FieldError>,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NotFound(path) => {
+                write!(f, "設定ファイルが見つかりません: {}", path.display())
+            }
+            ConfigError::Io { path, source } => {
+                write!(
+                    f,
+                    "設定ファイルの読み込みに失敗しました ({}): {}",
+                    path.display(),
+                    source
+                )
+            }
+            ConfigError::Parse { path, source } => {
+                write!(
+                    f,
+                    "設定ファイルのパースに失敗しました ({}):\n{}",
+                    path.display(),
+                    source
+                )
+            }
+            ConfigError::Invalid { path, errors } => {
+                match path {
+                    Some(path) => write!(f, "設定に誤りがあります ({}):", path.display())?,
+                    None => write!(
+                        f,
+                        "設定ファイルが見つからず、環境変数だけでは設定が不足しています:"
+                    )?,
+                }
+                for err in errors {
+                    write!(f, "\n  - {}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------- 読み込み処理 -------------------------------
+/// 設定ファイルを探す場所。優先度の高い順に並ぶ
+fn candidate_paths() -> Vec<PathBuf> {
+    let mut paths = vec![
+        PathBuf::from(CONFIG_FILE_NAME),
+        Path::new("src").join(CONFIG_FILE_NAME),
+    ];
+    let xdg_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    if let Some(dir) = xdg_dir {
+        paths.push(dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME));
+    }
+    paths
+}
+
+/// 使用する設定ファイルを決定する
+///
+/// `--config` → `VH1_CONFIG` → カレントディレクトリ → XDG config の順に探す。
+/// 明示的に指定されたパスが存在しない場合はエラー、探索で見つからない場合は `None`。
+fn resolve_path(cli_path: Option<&Path>) -> Result<Option<PathBuf>, ConfigError> {
+    let explicit = cli_path
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os(CONFIG_PATH_ENV).map(PathBuf::from));
+    if let Some(path) = explicit {
+        return if path.is_file() {
+            Ok(Some(path))
+        } else {
+            Err(ConfigError::NotFound(path))
+        };
+    }
+    Ok(candidate_paths().into_iter().find(|path| path.is_file()))
+}
+
+/// 環境変数の値を設定に反映する関数 (値が不正なら理由を返す)
+type ApplyOverride = fn(&mut Database, String) -> Result<(), String>;
+
+/// 環境変数で個別に上書きできる項目 (環境変数, 項目名, 反映する関数)
+const ENV_OVERRIDES: &[(&str, &str, ApplyOverride)] = &[
+    ("VH1_TOKEN", "token.token", |data, value| {
+        data.token.token = value;
+        Ok(())
+    }),
+    ("VH1_API_KEY", "token.api_key", |data, value| {
+        data.token.api_key = value;
+        Ok(())
+    }),
+    (
+        "VH1_API_ENDPOINT",
+        "endpoint.api_endpoint",
+        |data, value| {
+            data.endpoint.api_endpoint = value;
+            Ok(())
+        },
+    ),
+    ("VH1_TRANSLATE_JA", "id.translate_ja", |data, value| {
+        data.id.translate_ja = parse_id(&value)?;
+        Ok(())
+    }),
+    ("VH1_TRANSLATE_EN", "id.translate_en", |data, value| {
+        data.id.translate_en = parse_id(&value)?;
+        Ok(())
+    }),
+];
+
+/// パスワード未指定の Lavalink ノードに使われる環境変数
+const LAVALINK_PASSWORD_ENV: &str = "VH1_LAVALINK_PASSWORD";
+
+fn parse_id(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("値 {:?} は整数ではありません", value))
+}
+
+impl Database {
+    /// 環境変数による上書きを適用する
+    fn apply_env_overrides(&mut self, errors: &mut Vec<FieldError>) {
+        for &(var, key, apply) in ENV_OVERRIDES {
+            let Ok(value) = std::env::var(var) else {
+                continue;
+            };
+            if let Err(reason) = apply(self, value) {
+                errors.push(FieldError {
+                    key: key.to_string(),
+                    reason: format!("環境変数 {}: {}", var, reason),
+                });
+            }
+        }
+
+        // Lavalink ノードのパスワードは環境変数から補完する
+        for (i, node) in self.lavalink.nodes.iter_mut().enumerate() {
+            if let Some(var) = &node.password_env {
+                match std::env::var(var) {
+                    Ok(password) => node.password = password,
+                    Err(_) => errors.push(FieldError {
+                        key: format!("lavalink.nodes[{}].password_env", i),
+                        reason: format!("環境変数 {} が設定されていません", var),
+                    }),
+                }
+            } else if node.password.is_empty() {
+                if let Ok(password) = std::env::var(LAVALINK_PASSWORD_ENV) {
+                    node.password = password;
+                }
+            }
+        }
+    }
+
+    /// 必須項目と値の妥当性を検証する
+    fn validate(&self, errors: &mut Vec<FieldError>) {
+        let mut require = |key: &str, ok: bool, reason: &str| {
+            if !ok {
+                errors.push(FieldError {
+                    key: key.to_string(),
+                    reason: reason.to_string(),
+                });
+            }
+        };
+        require(
+            "token.token",
+            !self.token.token.trim().is_empty(),
+            "Discord Bot のトークンが設定されていません (VH1_TOKEN でも指定可)",
+        );
+        match self.translation.backend {
+            BackendKind::Deepl => {
+                require(
+                    "token.api_key",
+                    !self.token.api_key.trim().is_empty(),
+                    "DeepL の API キーが設定されていません (VH1_API_KEY でも指定可)",
+                );
+                // 空の場合は deepl_plan から接続先を決める
+                let endpoint = self.endpoint.api_endpoint.trim();
+                require(
+                    "endpoint.api_endpoint",
+                    endpoint.is_empty()
+                        || endpoint.starts_with("http://")
+                        || endpoint.starts_with("https://"),
+                    "http:// または https:// で始まる URL を指定してください",
+                );
+            }
+            BackendKind::LibreTranslate => {
+                let url = self.translation.libretranslate.url.trim();
+                require(
+                    "translation.libretranslate.url",
+                    url.starts_with("http://") || url.starts_with("https://"),
+                    "LibreTranslate の URL を http:// または https:// で指定してください",
+                );
+            }
+            BackendKind::Mock => {}
+        }
+        require(
+            "translation.usage.thresholds",
+            self.translation
+                .usage
+                .thresholds
+                .iter()
+                .all(|threshold| (1..=100).contains(threshold)),
+            "1〜100 の範囲で指定してください",
+        );
+        require(
+            "translation.usage.check_interval_secs",
+            self.translation.usage.check_interval_secs > 0,
+            "1 以上を指定してください",
+        );
+
+        if self.lavalink.nodes.is_empty() {
+            errors.push(FieldError {
+                key: "lavalink.nodes".to_string(),
+                reason: "少なくとも1つのノードを設定してください".to_string(),
+            });
+        }
+        for (i, node) in self.lavalink.nodes.iter().enumerate() {
+            if node.hostname.trim().is_empty() {
+                errors.push(FieldError {
+                    key: format!("lavalink.nodes[{}].hostname", i),
+                    reason: "ホスト名が設定されていません".to_string(),
+                });
+            }
+            if node.port == 0 {
+                errors.push(FieldError {
+                    key: format!("lavalink.nodes[{}].port", i),
+                    reason: "ポート番号が設定されていません".to_string(),
+                });
+            }
+            if node.password.is_empty() && node.password_env.is_none() {
+                errors.push(FieldError {
+                    key: format!("lavalink.nodes[{}].password", i),
+                    reason: format!(
+                        "パスワードが設定されていません (password_env か {} で指定してください)",
+                        LAVALINK_PASSWORD_ENV
+                    ),
+                });
+            }
+        }
+
+        if self.commands.register_on_start == RegisterMode::DevGuilds
+            && self.commands.dev_guilds.is_empty()
+        {
+            errors.push(FieldError {
+                key: "commands.dev_guilds".to_string(),
+                reason: "register_on_start = \"dev_guilds\" の場合はギルド ID を指定してください"
+                    .to_string(),
+            });
+        }
+        if self.commands.dev_guilds.contains(&0) {
+            errors.push(FieldError {
+                key: "commands.dev_guilds".to_string(),
+                reason: "0 はギルド ID として使用できません".to_string(),
+            });
+        }
+
+        let process = &self.lavalink.process;
+        if process.enabled {
+            match process.node {
+                Some(index) if index >= self.lavalink.nodes.len() => errors.push(FieldError {
+                    key: "lavalink.process.node".to_string(),
+                    reason: format!("ノード番号 {} は存在しません", index),
+                }),
+                None if self.lavalink.supervised_node().is_none() => errors.push(FieldError {
+                    key: "lavalink.process.node".to_string(),
+                    reason: "ローカルのノードが無いため、監視対象のノード番号を指定してください"
+                        .to_string(),
+                }),
+                _ => {}
+            }
+            if process.java.as_os_str().is_empty() {
+                errors.push(FieldError {
+                    key: "lavalink.process.java".to_string(),
+                    reason: "java の実行ファイルが設定されていません".to_string(),
+                });
+            }
+            if process.jar.as_os_str().is_empty() {
+                errors.push(FieldError {
+                    key: "lavalink.process.jar".to_string(),
+                    reason: "Lavalink.jar のパスが設定されていません".to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// 設定ファイルを探索して読み込み、環境変数の上書きと検証を行う
+pub fn load(cli_path: Option<&Path>) -> Result<(Option<PathBuf>, Database), ConfigError> {
+    let path = resolve_path(cli_path)?;
+    let mut data = match &path {
+        Some(path) => {
+            let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            toml::from_str(&content).map_err(|source| ConfigError::Parse {
+                path: path.clone(),
+                source,
+            })?
+        }
+        None => Database::default(),
+    };
+
+    let mut errors = Vec::new();
+    data.apply_env_overrides(&mut errors);
+    data.validate(&mut errors);
+    if !errors.is_empty() {
+        return Err(ConfigError::Invalid { path, errors });
+    }
+    Ok((path, data))
+}
+
+// ------------------------------- グローバル設定 -------------------------------
+struct GlobalConfig {
+    /// 起動時に `--config` で指定されたパス (再読み込み時も同じ探索を行う)
+    cli_path: Option<PathBuf>,
+    /// 実際に読み込んだ設定ファイル
+    path: RwLock<Option<PathBuf>>,
+    data: RwLock<Arc<Database>>,
+}
+
+static GLOBAL_DATA: OnceCell<GlobalConfig> = OnceCell::new();
+
+/// 読み込んだ設定をグローバルに登録する (起動時に一度だけ呼ぶ)
+pub fn init(cli_path: Option<PathBuf>, path: Option<PathBuf>, data: Database) {
+    let global = GlobalConfig {
+        cli_path,
+        path: RwLock::new(path),
+        data: RwLock::new(Arc::new(data)),
+    };
+    if GLOBAL_DATA.set(global).is_err() {
+        tracing::warn!("設定は既に初期化されています");
+    }
+}
+
+fn global_config() -> &'static GlobalConfig {
+    GLOBAL_DATA
+        .get()
+        .expect("config::init が呼ばれる前に設定を参照しました")
+}
+
+/// 現在の設定を取得する
+///
+/// 再読み込みされる可能性があるため、保持し続けずに都度呼び出すこと。
+pub fn global() -> Arc<Database> {
+    Arc::clone(&global_config().data.read().unwrap())
+}
+
+/// 現在読み込んでいる設定ファイルのパス
+pub fn loaded_path() -> Option<PathBuf> {
+    global_config().path.read().unwrap().clone()
+}
+
+/// 設定を再読み込みする
+///
+/// 失敗した場合は現在の設定を維持する。成功した場合は、
+/// 再起動しないと反映されない項目が変わっていればその一覧を返す。
+pub fn reload() -> Result<Vec<String>, ConfigError> {
+    let global = global_config();
+    let (path, data) = load(global.cli_path.as_deref())?;
+
+    let mut needs_restart = Vec::new();
+    {
+        let current = global.data.read().unwrap();
+        if current.token.token != data.token.token {
+            needs_restart.push("token.token".to_string());
+        }
+        if current.lavalink != data.lavalink {
+            needs_restart.push("lavalink".to_string());
+        }
+        if current.translation.cache != data.translation.cache {
+            needs_restart.push("translation.cache".to_string());
+        }
+    }
+
+    *global.path.write().unwrap() = path;
+    *global.data.write().unwrap() = Arc::new(data);
+    Ok(needs_restart)
+}
+
+/// 再読み込みの結果をログに出し、GUI やコマンド向けの文言を返す
+pub fn reload_and_report() -> Result<String, String> {
+    match reload() {
+        Ok(needs_restart) if needs_restart.is_empty() => {
+            tracing::info!("設定を再読み込みしました。");
+            Ok("設定を再読み込みしました。".to_string())
+        }
+        Ok(needs_restart) => {
+            let message = format!(
+                "設定を再読み込みしました。次の項目は再起動後に反映されます: {}",
+                needs_restart.join(", ")
+            );
+            tracing::warn!("{}", message);
+            Ok(message)
+        }
+        Err(err) => {
+            tracing::error!("設定の再読み込みに失敗しました: {}", err);
+            Err(format!(
+                "設定の再読み込みに失敗しました。現在の設定を維持します。\n{}",
+                err
+            ))
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// 設定ファイルの更新を監視し、変更されたら自動で再読み込みする
+pub fn spawn_watcher(interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last_modified = loaded_path().as_deref().and_then(modified_time);
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let Some(path) = loaded_path() else {
+                continue;
+            };
+            let modified = modified_time(&path);
+            if modified.is_some() && modified != last_modified {
+                last_modified = modified;
+                tracing::info!("設定ファイルの変更を検知しました: {}", path.display());
+                let _ = reload_and_report();
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 検証を通る最小限の設定
+    fn valid() -> Database {
+        toml::from_str(
+            r#"
+            [token]
+            token = "discord-token"
+            api_key = "deepl-key"
+
+            [[lavalink.nodes]]
+            hostname = "localhost"
+            port = 2333
+            password = "youshallnotpass"
+            "#,
+        )
+        .unwrap()
+    }
+
+    /// 検証で指摘された項目名
+    fn invalid_keys(data: &Database) -> Vec<String> {
+        let mut errors = Vec::new();
+        data.validate(&mut errors);
+        errors.into_iter().map(|error| error.key).collect()
+    }
+
+    #[test]
+    fn minimal_settings_are_valid() {
+        assert!(invalid_keys(&valid()).is_empty());
+    }
+
+    #[test]
+    fn unset_translate_roles_are_allowed() {
+        let data = valid();
+        assert_eq!((data.id.translate_ja, data.id.translate_en), (0, 0));
+        assert!(invalid_keys(&data).is_empty());
+    }
+
+    #[test]
+    fn missing_tokens_are_reported() {
+        let mut data = valid();
+        data.token.token = " ".to_string();
+        data.token.api_key.clear();
+        assert_eq!(invalid_keys(&data), ["token.token", "token.api_key"]);
+    }
+
+    #[test]
+    fn mock_backend_needs_no_api_key() {
+        let mut data = valid();
+        data.token.api_key.clear();
+        data.translation.backend = BackendKind::Mock;
+        assert!(invalid_keys(&data).is_empty());
+    }
+
+    #[test]
+    fn env_overrides_parse_ids() {
+        let apply = |key: &str, data: &mut Database, value: &str| {
+            let (_, _, apply) = ENV_OVERRIDES.iter().find(|(_, k, _)| *k == key).unwrap();
+            apply(data, value.to_string())
+        };
+        let mut data = valid();
+        assert!(apply("id.translate_ja", &mut data, " 123 ").is_ok());
+        assert_eq!(data.id.translate_ja, 123);
+        assert!(apply("id.translate_en", &mut data, "abc").is_err());
+        assert_eq!(data.id.translate_en, 0);
+        assert!(apply("token.token", &mut data, "other").is_ok());
+        assert_eq!(data.token.token, "other");
+    }
+
+    #[test]
+    fn invalid_values_are_reported() {
+        let mut data = valid();
+        data.endpoint.api_endpoint = "api.deepl.com".to_string();
+        data.translation.usage.thresholds = vec![50, 120];
+        data.lavalink.nodes[0].port = 0;
+        data.commands.dev_guilds = vec![0];
+        assert_eq!(
+            invalid_keys(&data),
+            [
+                "endpoint.api_endpoint",
+                "translation.usage.thresholds",
+                "lavalink.nodes[0].port",
+                "commands.dev_guilds",
+            ]
+        );
+    }
+}
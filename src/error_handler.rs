@@ -0,0 +1,203 @@
+use poise::serenity_prelude::{Color, CreateEmbed};
+use poise::FrameworkError;
+use std::fmt;
+
+use crate::guild_settings::Locale;
+use crate::{Context, Data, Error};
+
+/// 利用者にそのまま見せてよいエラー
+///
+/// チェックやコマンドからこれを返すと、エラー ID を付けずに内容だけを
+/// サーバーの言語設定に合わせて表示する。
+#[derive(Debug)]
+pub struct UserError {
+    pub ja: String,
+    pub en: String,
+}
+
+impl UserError {
+    pub fn new(ja: impl Into<String>, en: impl Into<String>) -> Self {
+        Self {
+            ja: ja.into(),
+            en: en.into(),
+        }
+    }
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.ja)
+    }
+}
+
+impl std::error::Error for UserError {}
+
+/// ログと照合するための短いエラー ID
+fn new_error_id() -> String {
+    format!("{:08X}", rand::random::<u32>())
+}
+
+fn locale_of(ctx: &Context<'_>) -> Locale {
+    ctx.guild_id()
+        .map(|guild_id| ctx.data().guild_settings.get(guild_id).locale)
+        .unwrap_or_default()
+}
+
+/// 表示する文言 (日本語, 英語)
+fn localize(locale: Locale, ja: String, en: String) -> String {
+    match locale {
+        Locale::Ja => ja,
+        Locale::En => en,
+    }
+}
+
+/// エラー用の埋め込みを一時メッセージで返信する
+async fn reply_error(ctx: Context<'_>, title: &str, description: String) {
+    let embed = CreateEmbed::new()
+        .title(title)
+        .color(Color::RED)
+        .description(description);
+    let reply = poise::CreateReply::default().embed(embed).ephemeral(true);
+    if let Err(err) = ctx.send(reply).await {
+        tracing::warn!("エラーメッセージの送信に失敗しました: {:?}", err);
+    }
+}
+
+/// 内部エラーとして ID を付けて記録し、利用者には ID だけを伝える
+async fn report_internal(ctx: Context<'_>, error: &(dyn std::error::Error + Send + Sync)) {
+    let error_id = new_error_id();
+    tracing::error!(
+        error_id = %error_id,
+        command = %ctx.command().qualified_name,
+        author = %ctx.author().id,
+        guild = ?ctx.guild_id(),
+        "コマンドの実行中にエラーが発生しました: {:?}",
+        error
+    );
+    let locale = locale_of(&ctx);
+    let title = localize(locale, "エラー".into(), "Error".into());
+    let description = localize(
+        locale,
+        format!(
+            "コマンドの実行中に問題が発生しました。\n管理者に問い合わせる際はエラー ID `{}` をお伝えください。",
+            error_id
+        ),
+        format!(
+            "Something went wrong while running this command.\nPlease include error ID `{}` when contacting an administrator.",
+            error_id
+        ),
+    );
+    reply_error(ctx, &title, description).await;
+}
+
+/// 利用者側の誤りなど、想定内のエラーを表示する
+async fn report_user(ctx: Context<'_>, ja: String, en: String) {
+    let locale = locale_of(&ctx);
+    let title = localize(locale, "実行できません".into(), "Cannot run command".into());
+    reply_error(ctx, &title, localize(locale, ja, en)).await;
+}
+
+/// フレームワーク全体のエラーハンドラ
+pub async fn on_error(error: FrameworkError<'_, Data, Error>) {
+    match error {
+        FrameworkError::Command { error, ctx, .. } => match error.downcast_ref::<UserError>() {
+            Some(user_error) => {
+                report_user(ctx, user_error.ja.clone(), user_error.en.clone()).await
+            }
+            None => report_internal(ctx, error.as_ref()).await,
+        },
+        FrameworkError::ArgumentParse {
+            error, input, ctx, ..
+        } => {
+            tracing::info!(
+                command = %ctx.command().qualified_name,
+                "引数の解析に失敗しました: {} (input: {:?})",
+                error,
+                input
+            );
+            let usage = format!("`{}{}`", ctx.prefix(), ctx.command().qualified_name);
+            report_user(
+                ctx,
+                format!("引数が正しくありません: {}\n使い方: {}", error, usage),
+                format!("Invalid arguments: {}\nUsage: {}", error, usage),
+            )
+            .await;
+        }
+        FrameworkError::CommandCheckFailed { error, ctx, .. } => match error {
+            Some(error) => match error.downcast_ref::<UserError>() {
+                Some(user_error) => {
+                    report_user(ctx, user_error.ja.clone(), user_error.en.clone()).await
+                }
+                None => report_internal(ctx, error.as_ref()).await,
+            },
+            None => {
+                report_user(
+                    ctx,
+                    "このコマンドを実行する権限がありません。".into(),
+                    "You are not allowed to use this command.".into(),
+                )
+                .await
+            }
+        },
+        FrameworkError::CooldownHit {
+            remaining_cooldown,
+            ctx,
+            ..
+        } => {
+            let secs = remaining_cooldown.as_secs_f32().ceil();
+            report_user(
+                ctx,
+                format!("クールダウン中です。あと {} 秒お待ちください。", secs),
+                format!("This command is on cooldown. Try again in {} s.", secs),
+            )
+            .await;
+        }
+        FrameworkError::MissingUserPermissions {
+            missing_permissions,
+            ctx,
+            ..
+        } => {
+            let missing =
+                missing_permissions.map_or("?".to_string(), |permissions| permissions.to_string());
+            report_user(
+                ctx,
+                format!("このコマンドには次の権限が必要です: {}", missing),
+                format!("You need the following permissions: {}", missing),
+            )
+            .await;
+        }
+        FrameworkError::MissingBotPermissions {
+            missing_permissions,
+            ctx,
+            ..
+        } => {
+            report_user(
+                ctx,
+                format!("Bot に次の権限がありません: {}", missing_permissions),
+                format!("The bot is missing permissions: {}", missing_permissions),
+            )
+            .await;
+        }
+        FrameworkError::NotAnOwner { ctx, .. } => {
+            report_user(
+                ctx,
+                "このコマンドは Bot のオーナーのみ使用できます。".into(),
+                "Only the bot owner can use this command.".into(),
+            )
+            .await;
+        }
+        FrameworkError::GuildOnly { ctx, .. } => {
+            report_user(
+                ctx,
+                "このコマンドはサーバー内でのみ使用できます。".into(),
+                "This command can only be used in a server.".into(),
+            )
+            .await;
+        }
+        error => {
+            if let Err(err) = poise::builtins::on_error(error).await {
+                tracing::error!("エラー処理中にエラーが発生しました: {:?}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use dashmap::DashMap;
+use poise::serenity_prelude::{ChannelId, GuildId, RoleId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::translation::GlossaryChoice;
+
+/// ギルド設定の保存先ディレクトリ (ギルドごとに `<guild_id>.json`)
+pub const GUILD_SETTINGS_DIR: &str = "data/guilds";
+/// ギルド設定が無い場合のプレフィックス
+pub const DEFAULT_PREFIX: &str = "s!";
+
+/// ギルドごとの設定
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// プレフィックスコマンドの接頭辞 (未設定なら `s!`)
+    pub prefix: Option<String>,
+    /// 自動翻訳の対象ロールと翻訳先言語 (空なら設定ファイルの `[id]` を使う)
+    pub translation_roles: Vec<TranslationRole>,
+    /// チャンネル単位の自動翻訳 (そのチャンネルの全メッセージが対象)
+    pub translation_channels: Vec<TranslationChannel>,
+    /// 再生操作を許可するロール (未設定なら誰でも操作可)
+    pub dj_role: Option<RoleId>,
+    /// プレイヤー作成時の音量
+    pub default_volume: Option<u16>,
+    /// 再生開始などの通知を送るチャンネル (未設定ならコマンドを実行したチャンネル)
+    pub notification_channel: Option<ChannelId>,
+    /// Bot の応答に使う言語
+    pub locale: Locale,
+    /// 言語の組ごとの用語集
+    pub glossaries: Vec<Glossary>,
+    /// 互いに翻訳して転送し合うチャンネルの組
+    pub bridges: Vec<Bridge>,
+    /// メッセージの編集・削除やメンバーの出入りを通知するチャンネル (未設定なら記録のみ)
+    pub audit_channel: Option<ChannelId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TranslationRole {
+    pub role_id: RoleId,
+    /// DeepL の target_lang (例: "JA", "EN-US")
+    pub target_lang: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TranslationChannel {
+    pub channel_id: ChannelId,
+    /// DeepL の target_lang の一覧
+    pub target_langs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bridge {
+    pub channel_a: ChannelId,
+    /// channel_a で使われる言語 (channel_b からの転送はこの言語に翻訳する)
+    pub lang_a: String,
+    pub channel_b: ChannelId,
+    pub lang_b: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Glossary {
+    /// 原文の言語 (DeepL の言語コード、地域なし。例: "JA")
+    pub source_lang: String,
+    /// 翻訳先の言語 (例: "EN")
+    pub target_lang: String,
+    /// 用語 → 訳語
+    pub entries: BTreeMap<String, String>,
+    /// 翻訳サービスに同期した用語集の ID (未同期なら None)
+    pub glossary_id: Option<String>,
+    /// `glossary_id` を作成した翻訳サービスの名前 (別のサービスに切り替えた後は使わない)
+    pub backend: Option<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, poise::ChoiceParameter,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    #[name = "日本語"]
+    Ja,
+    #[name = "English"]
+    En,
+}
+
+impl GuildSettings {
+    pub fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or(DEFAULT_PREFIX)
+    }
+
+    /// 翻訳先の言語に使える用語集 (原文の言語ごとに1つ)
+    ///
+    /// 用語集は言語の組ごとに作るため、どれを使うかは原文の言語が分かってから
+    /// [`crate::sub_command::translate`] で決める。
+    ///
+    /// 未同期のものと、`backend` 以外の翻訳サービスで作成したものは除く。
+    pub fn glossaries_for(&self, target_lang: &str, backend: &str) -> Vec<GlossaryChoice<'_>> {
+        let target = target_lang.split('-').next().unwrap_or("").to_uppercase();
+        self.glossaries
+            .iter()
+            .filter(|glossary| glossary.target_lang == target)
+            .filter(|glossary| glossary.backend.as_deref() == Some(backend))
+            .filter_map(|glossary| {
+                Some(GlossaryChoice {
+                    source_lang: &glossary.source_lang,
+                    glossary_id: glossary.glossary_id.as_deref()?,
+                })
+            })
+            .collect()
+    }
+
+    /// ブリッジの相手チャンネルと、転送時の翻訳先言語
+    pub fn bridge_partner(&self, channel_id: ChannelId) -> Option<(ChannelId, &str)> {
+        self.bridges.iter().find_map(|bridge| {
+            if bridge.channel_a == channel_id {
+                Some((bridge.channel_b, bridge.lang_b.as_str()))
+            } else if bridge.channel_b == channel_id {
+                Some((bridge.channel_a, bridge.lang_a.as_str()))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// チャンネルに設定された翻訳先言語
+    pub fn channel_targets(&self, channel_id: ChannelId) -> &[String] {
+        self.translation_channels
+            .iter()
+            .find(|rule| rule.channel_id == channel_id)
+            .map_or(&[], |rule| rule.target_langs.as_slice())
+    }
+}
+
+/// ギルド設定の永続化ストア
+pub type GuildSettingsStore = SettingsStore<GuildId, GuildSettings>;
+
+/// ID ごとの設定を JSON ファイルで永続化するストア
+///
+/// 読み込んだ設定はメモリにキャッシュし、更新時に JSON ファイルへ書き戻す。
+pub struct SettingsStore<K, V> {
+    dir: PathBuf,
+    cache: DashMap<K, V>,
+    locks: DashMap<K, Arc<tokio::sync::Mutex<()>>>,
+}
+
+impl<K, V> SettingsStore<K, V>
+where
+    K: Copy + Eq + Hash + Display,
+    V: Serialize + DeserializeOwned + Default + Clone,
+{
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            cache: DashMap::new(),
+            locks: DashMap::new(),
+        }
+    }
+
+    fn path_for(&self, id: K) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn load(path: &Path) -> V {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                tracing::warn!(
+                    "設定の読み込みに失敗しました ({}): {:?}",
+                    path.display(),
+                    err
+                );
+                V::default()
+            }),
+            Err(_) => V::default(),
+        }
+    }
+
+    /// 設定を取得する (未設定ならデフォルト値)
+    pub fn get(&self, id: K) -> V {
+        self.cache
+            .entry(id)
+            .or_insert_with(|| Self::load(&self.path_for(id)))
+            .clone()
+    }
+
+    /// ID ごとの排他ロックを取得する
+    ///
+    /// 外部サービスとの通信を挟んで設定を読み書きする場合に、
+    /// 読み込みから書き込みまでの間に他の更新が割り込まないようにする。
+    pub async fn lock(&self, id: K) -> tokio::sync::OwnedMutexGuard<()> {
+        let lock = Arc::clone(&self.locks.entry(id).or_default());
+        lock.lock_owned().await
+    }
+
+    /// 設定を変更してファイルに保存する
+    pub fn update<F>(&self, id: K, f: F) -> std::io::Result<V>
+    where
+        F: FnOnce(&mut V),
+    {
+        let path = self.path_for(id);
+        let mut entry = self.cache.entry(id).or_insert_with(|| Self::load(&path));
+        let mut settings = entry.clone();
+        f(&mut settings);
+
+        std::fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(&settings)?;
+        // 書き込み途中で落ちても壊れないよう、一時ファイルに書いてから置き換える
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, &path)?;
+
+        *entry = settings.clone();
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary(source_lang: &str, glossary_id: Option<&str>, backend: &str) -> Glossary {
+        Glossary {
+            source_lang: source_lang.to_string(),
+            target_lang: "EN".to_string(),
+            glossary_id: glossary_id.map(str::to_string),
+            backend: Some(backend.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn choice<'a>(source_lang: &'a str, glossary_id: &'a str) -> GlossaryChoice<'a> {
+        GlossaryChoice {
+            source_lang,
+            glossary_id,
+        }
+    }
+
+    #[test]
+    fn glossary_matches_target_without_region() {
+        let settings = GuildSettings {
+            glossaries: vec![glossary("JA", Some("g1"), "DeepL")],
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.glossaries_for("en-us", "DeepL"),
+            [choice("JA", "g1")]
+        );
+        assert!(settings.glossaries_for("DE", "DeepL").is_empty());
+    }
+
+    #[test]
+    fn glossaries_with_different_sources_are_all_candidates() {
+        let settings = GuildSettings {
+            glossaries: vec![
+                glossary("JA", Some("ja-en"), "DeepL"),
+                glossary("DE", Some("de-en"), "DeepL"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.glossaries_for("EN-GB", "DeepL"),
+            [choice("JA", "ja-en"), choice("DE", "de-en")]
+        );
+    }
+
+    #[test]
+    fn glossary_from_another_backend_is_ignored() {
+        let settings = GuildSettings {
+            glossaries: vec![glossary("JA", Some("g1"), "Mock")],
+            ..Default::default()
+        };
+        assert!(settings.glossaries_for("EN", "DeepL").is_empty());
+    }
+
+    #[test]
+    fn unsynced_glossary_is_skipped() {
+        let settings = GuildSettings {
+            glossaries: vec![
+                glossary("JA", None, "DeepL"),
+                glossary("DE", Some("g2"), "DeepL"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(settings.glossaries_for("EN", "DeepL"), [choice("DE", "g2")]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex};
+use tokio::{runtime::Runtime, sync::oneshot};
+use tracing_subscriber::{filter::LevelFilter, fmt, prelude::*};
+
+use crate::logging::RotatingLog;
+use crate::run_bot;
+
+/// ヘッドレスモードのログ出力先
+const BOT_LOG_PATH: &str = "logs/bot.log";
+
+/// 標準出力と logs/bot.log の両方にログを出す
+///
+/// logs/bot.log は [`append_log`](crate::logging::append_log) 経由で書き込み、ローテーションの対象にする。
+fn init_logging() {
+    let _ = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(fmt::layer())
+        .with(
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(RotatingLog(BOT_LOG_PATH)),
+        )
+        .try_init();
+}
+
+/// SIGINT (Ctrl+C) か SIGTERM を受け取るまで待つ
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => tracing::info!("SIGINT を受信しました。"),
+                    _ = sigterm.recv() => tracing::info!("SIGTERM を受信しました。"),
+                }
+            }
+            Err(err) => {
+                tracing::warn!("SIGTERM のハンドラ登録に失敗しました: {:?}", err);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        tracing::info!("Ctrl+C を受信しました。");
+    }
+}
+
+/// GUI を使わずに Bot を起動する (サーバー向け)
+///
+/// シグナルを受け取ると GUI の停止ボタンと同じく `shutdown_rx` 経由で停止する。
+pub fn run() -> Result<(), crate::Error> {
+    init_logging();
+
+    let rt = Runtime::new()?;
+    rt.block_on(async {
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            let _ = shutdown_tx.send(());
+        });
+
+        tracing::info!("ヘッドレスモードで起動します。");
+        run_bot(
+            shutdown_rx,
+            Arc::new(Mutex::new(Vec::new())),
+            Arc::new(Mutex::new(None)),
+            Arc::new(Mutex::new(Vec::new())),
+        )
+        .await
+    })
+}
@@ -0,0 +1,291 @@
+use chrono::{DateTime, Local, NaiveDate};
+use flate2::{write::GzEncoder, Compression};
+use once_cell::sync::Lazy;
+use std::fs::{self, create_dir_all, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::config::{self, LogSettings};
+
+/// 書き込みとローテーションを直列化するためのロック
+static LOG_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// ログファイルに 1 行追記する
+///
+/// `[logging]` の設定に従い、日付が変わったときやサイズを超えたときは
+/// 古いファイルを `<名前>.<日付>.log` に移してから書き込む。
+pub fn append_log<P: AsRef<Path>>(path: P, line: &str) {
+    let path = path.as_ref();
+    let settings = config::global().logging.clone();
+    let _guard = LOG_LOCK.lock().unwrap();
+
+    // フォルダが無ければ作成
+    if let Some(parent) = path.parent() {
+        let _ = create_dir_all(parent);
+    }
+    if let Some(date) = rotation_due(path, &settings, line.len() as u64 + 1) {
+        match rotate(path, date) {
+            Ok(rotated) => {
+                // 圧縮と古いファイルの削除は書き込みを待たせないよう別スレッドで行う
+                let path = path.to_path_buf();
+                std::thread::spawn(move || {
+                    if settings.compress {
+                        if let Err(err) = compress(&rotated) {
+                            eprintln!("[WARN] ログの圧縮に失敗しました ({:?}): {:?}", rotated, err);
+                        }
+                    }
+                    if settings.retention_days > 0 {
+                        remove_expired(&path, Duration::from_secs(settings.retention_days * 86400));
+                    }
+                });
+            }
+            Err(err) => eprintln!(
+                "[WARN] ログのローテーションに失敗しました ({:?}): {:?}",
+                path, err
+            ),
+        }
+    }
+    // 追記モードで開き、書き込み
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{line}");
+    }
+}
+
+/// tracing のログを [`append_log`] 経由で書き出す `MakeWriter`
+///
+/// 書き込み先のファイルも `[logging]` の設定でローテーションされる。
+pub struct RotatingLog(pub &'static str);
+
+impl<'a> MakeWriter<'a> for RotatingLog {
+    type Writer = LogEntry;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogEntry {
+            path: self.0,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// 1 イベント分の出力をためておき、書き終わったときにまとめて追記する
+pub struct LogEntry {
+    path: &'static str,
+    buf: Vec<u8>,
+}
+
+impl Write for LogEntry {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogEntry {
+    fn drop(&mut self) {
+        let text = String::from_utf8_lossy(&self.buf);
+        let text = text.trim_end_matches('\n');
+        if !text.is_empty() {
+            append_log(self.path, text);
+        }
+    }
+}
+
+/// ローテーションが必要なら、移動先の名前に使う日付 (最後に書き込んだ日) を返す
+fn rotation_due(path: &Path, settings: &LogSettings, incoming: u64) -> Option<NaiveDate> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() == 0 {
+        return None;
+    }
+    let modified = DateTime::<Local>::from(metadata.modified().ok()?).date_naive();
+    let new_day = settings.rotate_daily && modified != Local::now().date_naive();
+    let max_bytes = settings.max_size_mb * 1024 * 1024;
+    let too_large = max_bytes > 0 && metadata.len() + incoming > max_bytes;
+    (new_day || too_large).then_some(modified)
+}
+
+/// `logs/lavalink.log` → (`lavalink`, `log`)
+fn split_name(path: &Path) -> (String, String) {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "log".to_string());
+    (stem, extension)
+}
+
+/// 現在のファイルを `<名前>.<日付>[.<番号>].<拡張子>` に移す
+fn rotate(path: &Path, date: NaiveDate) -> io::Result<PathBuf> {
+    let (stem, extension) = split_name(path);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let date = date.format("%Y-%m-%d");
+    // 同じ日に何度もローテーションした場合は番号を付けて区別する
+    let rotated = (0..)
+        .map(|n| {
+            let name = if n == 0 {
+                format!("{stem}.{date}.{extension}")
+            } else {
+                format!("{stem}.{date}.{n}.{extension}")
+            };
+            dir.join(name)
+        })
+        .find(|candidate| !candidate.exists() && !gz_path(candidate).exists())
+        .expect("番号は無限に続く");
+    fs::rename(path, &rotated)?;
+    Ok(rotated)
+}
+
+fn gz_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// ファイルを gzip で圧縮し、元のファイルを削除する
+fn compress(path: &Path) -> io::Result<()> {
+    let target = gz_path(path);
+    let mut encoder = GzEncoder::new(File::create(&target)?, Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(path)
+}
+
+/// 保存期間を過ぎたローテーション済みのファイルを削除する
+fn remove_expired(path: &Path, retention: Duration) {
+    let (stem, _) = split_name(path);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let prefix = format!("{stem}.");
+    let now = SystemTime::now();
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // `<名前>.<日付>...` の形式のものだけを対象にする
+        let Some(rest) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+        let is_rotated = rest
+            .get(..10)
+            .is_some_and(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok());
+        if !is_rotated {
+            continue;
+        }
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > retention);
+        if expired {
+            if let Err(err) = fs::remove_file(entry.path()) {
+                eprintln!(
+                    "[WARN] 古いログの削除に失敗しました ({:?}): {:?}",
+                    entry.path(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テストごとの作業ディレクトリ
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vh1-logging-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(path: &Path, len: usize, modified: SystemTime) {
+        let file = File::create(path).unwrap();
+        file.set_len(len as u64).unwrap();
+        file.set_modified(modified).unwrap();
+    }
+
+    #[test]
+    fn missing_or_empty_file_is_not_rotated() {
+        let dir = temp_dir("empty");
+        let path = dir.join("bot.log");
+        let settings = LogSettings::default();
+        assert_eq!(rotation_due(&path, &settings, 10), None);
+        write_file(&path, 0, SystemTime::now() - Duration::from_secs(3 * 86400));
+        assert_eq!(rotation_due(&path, &settings, 10), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotates_on_new_day() {
+        let dir = temp_dir("daily");
+        let path = dir.join("bot.log");
+        let modified = SystemTime::now() - Duration::from_secs(3 * 86400);
+        write_file(&path, 10, modified);
+        let settings = LogSettings::default();
+        assert_eq!(
+            rotation_due(&path, &settings, 10),
+            Some(DateTime::<Local>::from(modified).date_naive())
+        );
+        let settings = LogSettings {
+            rotate_daily: false,
+            ..settings
+        };
+        assert_eq!(rotation_due(&path, &settings, 10), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotates_when_size_is_exceeded() {
+        let dir = temp_dir("size");
+        let path = dir.join("bot.log");
+        write_file(&path, 1024 * 1024 - 10, SystemTime::now());
+        let settings = LogSettings {
+            max_size_mb: 1,
+            ..Default::default()
+        };
+        assert_eq!(rotation_due(&path, &settings, 10), None);
+        assert_eq!(
+            rotation_due(&path, &settings, 11),
+            Some(Local::now().date_naive())
+        );
+        let settings = LogSettings {
+            max_size_mb: 0,
+            ..settings
+        };
+        assert_eq!(rotation_due(&path, &settings, 11), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotated_names_do_not_collide() {
+        let dir = temp_dir("rotate");
+        let path = dir.join("bot.log");
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        write_file(&path, 1, SystemTime::now());
+        assert_eq!(rotate(&path, date).unwrap(), dir.join("bot.2025-06-01.log"));
+        write_file(&path, 1, SystemTime::now());
+        assert_eq!(
+            rotate(&path, date).unwrap(),
+            dir.join("bot.2025-06-01.1.log")
+        );
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,84 +1,197 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod archive;
+mod audit;
+mod bridge;
 mod commands;
+mod config;
+mod error_handler;
+mod guild_settings;
+mod headless;
+mod logging;
+mod shutdown;
 mod sub_command;
+mod supervisor;
+mod translation;
+mod user_settings;
 
-use chrono::Local;
+use archive::{ArchiveQuery, ArchivedMessage};
+use chrono::{Local, NaiveDate};
+use config::RegisterMode;
+use dashmap::DashMap;
 use eframe::{egui, App, NativeOptions};
 use egui::{Vec2, ViewportBuilder};
+use futures::future::join_all;
+use guild_settings::{GuildSettingsStore, TranslationRole};
 use lavalink_rs::{model::events, prelude::*};
-use once_cell::sync::Lazy;
+use lru::LruCache;
+use once_cell::sync::OnceCell;
 use poise::serenity_prelude::{
-    async_trait, Client, Color, CreateEmbed, CreateMessage, EventHandler, GatewayIntents, Message,
-    MessageReference, Ready,
+    async_trait, ChannelId, Client, Color, CreateEmbed, CreateMessage, EditMessage, EventHandler,
+    GatewayIntents, GuildId, Message, MessageId, MessageReference, MessageUpdateEvent, Reaction,
+    ReactionType, Ready, RoleId,
 };
-use serde::Deserialize;
+use shutdown::{ShutdownCoordinator, ShutdownTargets};
 use songbird::{Config, SerenityInit};
-use std::fs::{create_dir_all, OpenOptions};
-use std::io::Write; // ← ここを追加！
-use std::process::Command as SysCommand;
+use std::fs::create_dir_all;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
 };
+use std::time::Duration;
 use sub_command::translate;
+use supervisor::LavalinkSupervisor;
 use tokio::{runtime::Runtime, sync::oneshot};
+use translation::{Origin, TranslateError, TranslateOptions};
+use user_settings::UserSettingsStore;
 
-// ------------------------------- 設定用構造体 -------------------------------
-#[derive(Deserialize, Debug)]
-struct Database {
-    token: Tokens,
-    endpoint: Endpoints,
-    id: Id,
-}
+/// 翻訳処理の結果を返す型
+type TranslationResult = (String, String);
 
-#[derive(Deserialize, Debug)]
-struct Tokens {
-    token: String,
-    api_key: String,
+// ------------------------------- イベントハンドラ類 -------------------------------
+struct Translate {
+    guild_settings: Arc<GuildSettingsStore>,
+    /// 上限到達の通知をそのチャンネルに最後に送った日付 (1日1回まで)
+    quota_notices: DashMap<ChannelId, NaiveDate>,
+    /// 翻訳元のメッセージ → Bot が送った翻訳の返信 (編集・削除に追従するため)
+    replies: Mutex<LruCache<MessageId, MessageId>>,
 }
 
-#[derive(Deserialize, Debug)]
-struct Endpoints {
-    api_endpoint: String,
-}
+/// 翻訳の返信を追跡するメッセージ数
+const TRANSLATION_REPLY_LIMIT: usize = 10_000;
 
-#[derive(Deserialize, Debug)]
-struct Id {
-    translate_ja: u64,
-    translate_en: u64,
-}
+impl Translate {
+    fn new(guild_settings: Arc<GuildSettingsStore>) -> Self {
+        Self {
+            guild_settings,
+            quota_notices: DashMap::new(),
+            replies: Mutex::new(LruCache::new(
+                NonZeroUsize::new(TRANSLATION_REPLY_LIMIT).unwrap(),
+            )),
+        }
+    }
 
-#[derive(Deserialize, Debug)]
-struct Translations {
-    detected_source_language: String,
-    text: String,
-}
+    /// 今日まだ通知していないチャンネルなら true を返して記録する
+    fn should_notify_quota(&self, channel_id: ChannelId) -> bool {
+        let today = Local::now().date_naive();
+        let previous = self.quota_notices.insert(channel_id, today);
+        previous != Some(today)
+    }
 
-#[derive(Deserialize, Debug)]
-struct TranslationResponse {
-    translations: Vec<Translations>,
-}
+    /// メッセージを設定に従って翻訳し、埋め込みの本文を返す
+    ///
+    /// 翻訳先が無ければ空文字列、文字数の上限に達した場合は None を返す。
+    async fn describe(
+        &self,
+        ctx: &poise::serenity_prelude::Context,
+        msg: &Message,
+        guild_id: GuildId,
+    ) -> Option<String> {
+        let guild_settings = self.guild_settings.get(guild_id);
+        // チャンネルのルールで決まる翻訳先
+        let mut target_langs: Vec<String> = guild_settings.channel_targets(msg.channel_id).to_vec();
 
-/// 翻訳処理の結果を返す型
-type TranslationResult = (String, String);
+        // ギルド設定に翻訳ロールがあればそれを、無ければ Bot 全体の設定を使う
+        let mut roles = guild_settings.translation_roles.clone();
+        if roles.is_empty() {
+            let settings = config::global();
+            // 未設定 (0) のロールは対象にしない
+            roles = [
+                (settings.id.translate_ja, "ja"),
+                (settings.id.translate_en, "en"),
+            ]
+            .into_iter()
+            .filter(|(role_id, _)| *role_id != 0)
+            .map(|(role_id, target_lang)| TranslationRole {
+                role_id: RoleId::new(role_id),
+                target_lang: target_lang.to_string(),
+            })
+            .collect();
+        }
+        let pending: Vec<&TranslationRole> = roles
+            .iter()
+            .filter(|role| {
+                !target_langs
+                    .iter()
+                    .any(|lang| lang.eq_ignore_ascii_case(&role.target_lang))
+            })
+            .collect();
+        if !pending.is_empty() {
+            let member_roles = author_roles(ctx, msg, guild_id).await;
+            for role in pending {
+                if member_roles.contains(&role.role_id) {
+                    target_langs.push(role.target_lang.clone());
+                }
+            }
+        }
+
+        let origin = Origin {
+            guild_id: Some(guild_id),
+            channel_id: msg.channel_id,
+        };
+        // 翻訳先ごとのリクエストはまとめて並行に送る
+        let content = msg.content.as_str();
+        let results = join_all(target_langs.iter().map(|lang| {
+            let glossaries =
+                guild_settings.glossaries_for(lang, translation::current_backend().name());
+            async move {
+                translate(
+                    content,
+                    lang,
+                    origin,
+                    &TranslateOptions::default(),
+                    &glossaries,
+                )
+                .await
+            }
+        }))
+        .await;
+
+        let mut description = String::new();
+        for (target_lang, result) in target_langs.iter().zip(results) {
+            let result: TranslationResult = match result {
+                Ok(result) => result,
+                Err(TranslateError::QuotaExceeded) => {
+                    // 上限に達したら以降の翻訳は諦め、チャンネルには1日1回だけ知らせる
+                    tracing::warn!("翻訳の文字数上限に達しました。");
+                    if self.should_notify_quota(msg.channel_id) {
+                        let embed = CreateEmbed::new().color(Color::ORANGE).description(
+                            "翻訳できる文字数の上限に達したため、自動翻訳を一時停止しています。",
+                        );
+                        let builder = CreateMessage::new().add_embed(embed);
+                        if let Err(err) = msg.channel_id.send_message(&ctx.http, builder).await {
+                            tracing::warn!("メッセージ送信エラー: {:?}", err);
+                        }
+                    }
+                    return None;
+                }
+                Err(err) => {
+                    tracing::warn!("{} への翻訳に失敗しました: {}", target_lang, err);
+                    continue;
+                }
+            };
+            // 元から翻訳先の言語で書かれていれば載せない
+            if translation::same_language(&result.0, target_lang) {
+                continue;
+            }
+            description.push_str(&translation_line(target_lang, &result));
+        }
+        Some(description)
+    }
+}
+/// GUI に保持するチャットログの最大行数
+const CHAT_LOG_LIMIT: usize = 1000;
 
-// ------------------------------- イベントハンドラ類 -------------------------------
-struct Translate;
 struct MessageLog {
     chat_messages: Arc<Mutex<Vec<String>>>,
 }
 
-static GLOBAL_DATA: Lazy<Database> = Lazy::new(|| {
-    let config_content = std::fs::read_to_string("D:/Programming/Rust/VH1-Bot/src/Setting.toml")
-        .expect("設定ファイルの読み込みに失敗しました");
-    toml::from_str(&config_content).expect("設定ファイルのパースに失敗しました")
-});
-
 #[async_trait]
 impl EventHandler for Translate {
     async fn ready(&self, _ctx: poise::serenity_prelude::Context, ready: Ready) {
-        println!("{} is connected!", ready.user.name);
+        tracing::info!("{} is connected!", ready.user.name);
     }
 
     async fn message(&self, ctx: poise::serenity_prelude::Context, msg: Message) {
@@ -86,55 +199,295 @@ impl EventHandler for Translate {
             return;
         }
 
+        if msg.content.trim().is_empty() {
+            return;
+        }
+
         if let Some(guild_id) = msg.guild_id {
-            let has_translate_ja = msg
-                .author
-                .has_role(&ctx.http, guild_id, GLOBAL_DATA.id.translate_ja)
-                .await
-                .unwrap_or(false);
-            let has_translate_en = msg
-                .author
-                .has_role(&ctx.http, guild_id, GLOBAL_DATA.id.translate_en)
-                .await
-                .unwrap_or(false);
+            let Some(description) = self.describe(&ctx, &msg, guild_id).await else {
+                return;
+            };
+            if !description.is_empty() {
+                let builder = translation_reply(&msg, description);
+                match msg.channel_id.send_message(&ctx.http, builder).await {
+                    Ok(reply) => {
+                        self.replies.lock().unwrap().put(msg.id, reply.id);
+                    }
+                    Err(err) => tracing::warn!("メッセージ送信エラー: {:?}", err),
+                }
+            }
+        }
+    }
 
-            if has_translate_ja || has_translate_en {
-                let mut description = String::new();
-                if has_translate_ja {
-                    let result: TranslationResult = translate(&msg.content, "ja").await;
-                    description.push_str(&format!("**日本語翻訳**\n{}: {}\n", result.0, result.1));
+    async fn message_update(
+        &self,
+        ctx: poise::serenity_prelude::Context,
+        _old_if_available: Option<Message>,
+        new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        // 本文が変わっていない更新 (埋め込みの展開など) は無視する
+        if event.content.is_none() {
+            return;
+        }
+        let Some(reply_id) = self.replies.lock().unwrap().get(&event.id).copied() else {
+            return;
+        };
+        let msg = match new {
+            Some(msg) => msg,
+            None => match event.channel_id.message(&ctx.http, event.id).await {
+                Ok(msg) => msg,
+                Err(err) => {
+                    tracing::warn!("編集されたメッセージを取得できません: {:?}", err);
+                    return;
                 }
-                if has_translate_en {
-                    let result: TranslationResult = translate(&msg.content, "en").await;
-                    description.push_str(&format!("**英語翻訳**\n{}: {}\n", result.0, result.1));
+            },
+        };
+        let Some(guild_id) = msg.guild_id.or(event.guild_id) else {
+            return;
+        };
+
+        let description = if msg.content.trim().is_empty() {
+            String::new()
+        } else {
+            match self.describe(&ctx, &msg, guild_id).await {
+                Some(description) => description,
+                // 上限に達した場合は前の翻訳を残す
+                None => return,
+            }
+        };
+        if description.is_empty() {
+            // 翻訳が不要になった場合は返信も消す
+            self.replies.lock().unwrap().pop(&event.id);
+            if let Err(err) = msg.channel_id.delete_message(&ctx.http, reply_id).await {
+                tracing::warn!("翻訳の返信を削除できません: {:?}", err);
+            }
+            return;
+        }
+        let builder = EditMessage::new().embed(translation_embed(&msg, description));
+        if let Err(err) = msg
+            .channel_id
+            .edit_message(&ctx.http, reply_id, builder)
+            .await
+        {
+            tracing::warn!("翻訳の返信を更新できません: {:?}", err);
+        }
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: poise::serenity_prelude::Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) {
+        let Some(reply_id) = self.replies.lock().unwrap().pop(&deleted_message_id) else {
+            return;
+        };
+        if let Err(err) = channel_id.delete_message(&ctx.http, reply_id).await {
+            tracing::warn!("翻訳の返信を削除できません: {:?}", err);
+        }
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: poise::serenity_prelude::Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        _guild_id: Option<GuildId>,
+    ) {
+        let reply_ids: Vec<MessageId> = {
+            let mut replies = self.replies.lock().unwrap();
+            multiple_deleted_messages_ids
+                .iter()
+                .filter_map(|id| replies.pop(id))
+                .collect()
+        };
+        // 一括削除は 2〜100 件ずつしか受け付けない
+        for chunk in reply_ids.chunks(100) {
+            let result = match chunk {
+                [reply_id] => channel_id.delete_message(&ctx.http, *reply_id).await,
+                _ => channel_id.delete_messages(&ctx.http, chunk).await,
+            };
+            if let Err(err) = result {
+                tracing::warn!("翻訳の返信を削除できません: {:?}", err);
+            }
+        }
+    }
+}
+
+/// 国旗のリアクションで翻訳するハンドラ
+struct ReactionTranslate {
+    guild_settings: Arc<GuildSettingsStore>,
+    /// 翻訳済みの (メッセージ, 言語)。同じ国旗が何度付いても翻訳し直さない
+    translated: Mutex<LruCache<(MessageId, String), ()>>,
+}
+
+/// 翻訳済みとして覚えておく (メッセージ, 言語) の件数
+const REACTION_HISTORY_LIMIT: usize = 10_000;
+
+impl ReactionTranslate {
+    fn new(guild_settings: Arc<GuildSettingsStore>) -> Self {
+        Self {
+            guild_settings,
+            translated: Mutex::new(LruCache::new(
+                NonZeroUsize::new(REACTION_HISTORY_LIMIT).unwrap(),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for ReactionTranslate {
+    async fn reaction_add(&self, ctx: poise::serenity_prelude::Context, reaction: Reaction) {
+        let ReactionType::Unicode(emoji) = &reaction.emoji else {
+            return;
+        };
+        let Some(target_lang) = translation::target_lang_for_flag(emoji) else {
+            return;
+        };
+        if reaction.user_id == Some(ctx.cache.current_user().id) {
+            return;
+        }
+        // 他の Bot が付けた国旗では翻訳しない
+        let is_bot = match &reaction.member {
+            Some(member) => member.user.bot,
+            None => match reaction.user(&ctx).await {
+                Ok(user) => user.bot,
+                Err(err) => {
+                    tracing::warn!("リアクションしたユーザーを取得できません: {:?}", err);
+                    return;
                 }
+            },
+        };
+        if is_bot {
+            return;
+        }
 
-                if !description.is_empty() {
-                    let embed = CreateEmbed::new()
-                        .title(&msg.author.name)
-                        .color(Color::DARK_BLUE)
-                        .description(description);
-                    let msg_ref = MessageReference::from(&msg);
-                    let builder = CreateMessage::new()
-                        .add_embed(embed)
-                        .reference_message(msg_ref);
-                    if let Err(err) = msg.channel_id.send_message(&ctx.http, builder).await {
-                        eprintln!("メッセージ送信エラー: {:?}", err);
-                    }
+        let key = (reaction.message_id, target_lang.to_string());
+        if self
+            .translated
+            .lock()
+            .unwrap()
+            .put(key.clone(), ())
+            .is_some()
+        {
+            return;
+        }
+
+        let cached = ctx
+            .cache
+            .message(reaction.channel_id, reaction.message_id)
+            .map(|msg| msg.clone());
+        let msg = match cached {
+            Some(msg) => msg,
+            None => match reaction.message(&ctx.http).await {
+                Ok(msg) => msg,
+                Err(err) => {
+                    tracing::warn!("リアクション先のメッセージを取得できません: {:?}", err);
+                    self.translated.lock().unwrap().pop(&key);
+                    return;
                 }
+            },
+        };
+        if msg.content.trim().is_empty() {
+            return;
+        }
+
+        let origin = Origin {
+            guild_id: msg.guild_id,
+            channel_id: msg.channel_id,
+        };
+        let guild_settings = msg
+            .guild_id
+            .map(|guild_id| self.guild_settings.get(guild_id))
+            .unwrap_or_default();
+        let glossaries =
+            guild_settings.glossaries_for(target_lang, translation::current_backend().name());
+        let options = TranslateOptions::default();
+        let result = match translate(&msg.content, target_lang, origin, &options, &glossaries).await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                tracing::warn!("{} への翻訳に失敗しました: {}", target_lang, err);
+                // 失敗した場合は同じ国旗で再試行できるようにする
+                self.translated.lock().unwrap().pop(&key);
+                return;
             }
+        };
+        if translation::same_language(&result.0, target_lang) {
+            return;
+        }
+
+        let builder = translation_reply(&msg, translation_line(target_lang, &result));
+        if let Err(err) = msg.channel_id.send_message(&ctx.http, builder).await {
+            tracing::warn!("メッセージ送信エラー: {:?}", err);
         }
     }
 }
 
-fn append_log<P: AsRef<std::path::Path>>(path: P, line: &str) {
-    // フォルダが無ければ作成
-    if let Some(parent) = path.as_ref().parent() {
-        let _ = create_dir_all(parent);
+/// 翻訳結果の埋め込みに載せる1言語分の文
+fn translation_line(target_lang: &str, result: &TranslationResult) -> String {
+    format!(
+        "**{}**\n{}: {}\n",
+        translation_label(target_lang),
+        result.0,
+        result.1
+    )
+}
+
+/// 翻訳結果の埋め込み
+fn translation_embed(msg: &Message, description: String) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(&msg.author.name)
+        .color(Color::DARK_BLUE)
+        .description(description)
+}
+
+/// 元のメッセージへの返信として翻訳結果の埋め込みを送る
+fn translation_reply(msg: &Message, description: String) -> CreateMessage {
+    CreateMessage::new()
+        .add_embed(translation_embed(msg, description))
+        .reference_message(MessageReference::from(msg))
+}
+
+/// 発言者のロール一覧
+///
+/// メッセージに付いているメンバー情報、キャッシュの順に探し、
+/// どちらにも無い場合だけ API から取得する。
+async fn author_roles(
+    ctx: &poise::serenity_prelude::Context,
+    msg: &Message,
+    guild_id: GuildId,
+) -> Vec<RoleId> {
+    if let Some(member) = &msg.member {
+        return member.roles.clone();
     }
-    // 追記モードで開き、書き込み
-    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
-        let _ = writeln!(file, "{line}");
+    let cached = ctx.cache.guild(guild_id).and_then(|guild| {
+        guild
+            .members
+            .get(&msg.author.id)
+            .map(|member| member.roles.clone())
+    });
+    if let Some(roles) = cached {
+        return roles;
+    }
+    match guild_id.member(&ctx.http, msg.author.id).await {
+        Ok(member) => member.roles,
+        Err(err) => {
+            tracing::warn!("メンバー情報の取得に失敗しました: {:?}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// 翻訳結果の見出し
+fn translation_label(target_lang: &str) -> String {
+    let lang = target_lang.to_uppercase();
+    match lang.split('-').next() {
+        Some("JA") => "日本語翻訳".to_string(),
+        Some("EN") => "英語翻訳".to_string(),
+        _ => format!("{} 翻訳", lang),
     }
 }
 
@@ -189,32 +542,50 @@ impl EventHandler for MessageLog {
         {
             let mut messages = self.chat_messages.lock().unwrap();
             messages.push(line.clone());
+            if messages.len() > CHAT_LOG_LIMIT {
+                messages.remove(0);
+            }
+        }
+        if let Err(err) = archive::save(ArchivedMessage::from_message(&ctx.cache, &msg)).await {
+            tracing::warn!("メッセージの保存に失敗しました: {:?}", err);
         }
-        append_log("logs/discord_messages.log", &line); // ★追加
     }
 }
 
 // ------------------------------- Bot / Lavalink 用データ構造 -------------------------------
 struct Data {
     lavalink: LavalinkClient,
+    guild_settings: Arc<GuildSettingsStore>,
+    user_settings: Arc<UserSettingsStore>,
 }
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
 // ------------------------------- Bot 起動処理 -------------------------------
+/// 設定ファイルの更新を確認する間隔
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
 async fn run_bot(
     mut shutdown_rx: oneshot::Receiver<()>,
     log_buffer: Arc<Mutex<Vec<String>>>,
     pid_holder: Arc<Mutex<Option<u32>>>,
     chatmessage: Arc<Mutex<Vec<String>>>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // 停止処理で使うため、setup で生成した LavalinkClient をここにも保持する
+    let lavalink_slot: Arc<OnceCell<LavalinkClient>> = Arc::new(OnceCell::new());
+    let lavalink_slot_for_setup = Arc::clone(&lavalink_slot);
+
+    // ギルドごとの設定 (Data と翻訳ハンドラで共有する)
+    let guild_settings = Arc::new(GuildSettingsStore::new(guild_settings::GUILD_SETTINGS_DIR));
+    let guild_settings_for_setup = Arc::clone(&guild_settings);
+
     // フレームワークの生成
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             commands: vec![
                 sub_command::ping(),
-                sub_command::trans(),
+                sub_command::trans_command(),
                 commands::music::music_basic::play(),
                 commands::music::music_basic::join(),
                 commands::music::music_basic::leave(),
@@ -230,17 +601,53 @@ async fn run_bot(
                 commands::music::music_advanced::shuffle(),
                 commands::music::music_advanced::repeat(),
                 commands::test::button_test(),
+                commands::admin::admin(),
+                commands::guild_config::config(),
+                commands::archive::archive(),
+                commands::glossary::glossary(),
+                commands::translate::translate(),
+                commands::translate::translate_message(),
             ],
             prefix_options: poise::PrefixFrameworkOptions {
-                prefix: Some("s!".to_string()),
+                // プレフィックスはギルド設定から決める (未設定なら s!)
+                prefix: None,
+                dynamic_prefix: Some(|ctx| {
+                    Box::pin(async move {
+                        let prefix = match ctx.guild_id {
+                            Some(guild_id) => {
+                                ctx.data.guild_settings.get(guild_id).prefix().to_string()
+                            }
+                            None => guild_settings::DEFAULT_PREFIX.to_string(),
+                        };
+                        Ok(Some(prefix))
+                    })
+                }),
                 ..Default::default()
             },
+            on_error: |error| Box::pin(error_handler::on_error(error)),
             ..Default::default()
         })
         .setup(move |ctx, _ready, framework| {
             Box::pin(async move {
-                // グローバルコマンドの登録
-                poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+                // スラッシュコマンドの登録
+                let commands = &framework.options().commands;
+                let settings = config::global();
+                match settings.commands.register_on_start {
+                    RegisterMode::Global => {
+                        poise::builtins::register_globally(ctx, commands).await?;
+                    }
+                    RegisterMode::DevGuilds => {
+                        for guild_id in &settings.commands.dev_guilds {
+                            poise::builtins::register_in_guild(
+                                ctx,
+                                commands,
+                                GuildId::new(*guild_id),
+                            )
+                            .await?;
+                        }
+                    }
+                    RegisterMode::None => {}
+                }
 
                 // Lavalink のイベント設定
                 let events = events::Events {
@@ -252,137 +659,123 @@ async fn run_bot(
                 };
 
                 // Lavalinkノードの設定
-                let node_local = NodeBuilder {
-                    hostname: "localhost:2333".to_string(),
-                    is_ssl: false,
-                    events: events::Events::default(),
-                    password: "ncfhewau3a2rncu".to_string(),
-                    user_id: lavalink_rs::model::UserId::from(u64::from(
-                        ctx.cache.current_user().id,
-                    )),
-                    session_id: None,
-                };
+                let user_id =
+                    lavalink_rs::model::UserId::from(u64::from(ctx.cache.current_user().id));
+                let nodes = settings
+                    .lavalink
+                    .nodes
+                    .iter()
+                    .map(|node| NodeBuilder {
+                        hostname: node.address(),
+                        is_ssl: node.ssl,
+                        events: events::Events::default(),
+                        password: node.password.clone(),
+                        user_id,
+                        session_id: None,
+                    })
+                    .collect();
 
                 let client = LavalinkClient::new(
                     events,
-                    vec![node_local],
-                    NodeDistributionStrategy::round_robin(),
+                    nodes,
+                    settings.lavalink.strategy.to_distribution(),
                 )
                 .await;
 
-                Ok(Data { lavalink: client })
+                let _ = lavalink_slot_for_setup.set(client.clone());
+                Ok(Data {
+                    lavalink: client,
+                    guild_settings: guild_settings_for_setup,
+                    user_settings: Arc::new(UserSettingsStore::new(
+                        user_settings::USER_SETTINGS_DIR,
+                    )),
+                })
             })
         })
         .build();
 
     // Songbirdの設定
     let songbird_config = Config::default().decode_mode(songbird::driver::DecodeMode::Decode);
+    let songbird = songbird::Songbird::serenity_from_config(songbird_config);
 
     // Discord Clientの作成
     let mut client = Client::builder(
-        &GLOBAL_DATA.token.token,
+        &config::global().token.token,
         GatewayIntents::all() | GatewayIntents::GUILD_VOICE_STATES,
     )
     .event_handler(MessageLog {
         chat_messages: Arc::clone(&chatmessage),
     })
-    .event_handler(Translate)
+    .event_handler(Translate::new(Arc::clone(&guild_settings)))
+    .event_handler(ReactionTranslate::new(Arc::clone(&guild_settings)))
+    .event_handler(bridge::Bridge::new(Arc::clone(&guild_settings)))
+    .event_handler(audit::AuditLog::new(guild_settings))
     .framework(framework)
-    .register_songbird_from_config(songbird_config)
+    .register_songbird_with(Arc::clone(&songbird))
     .await
     .expect("Clientの作成に失敗しました");
 
-    // Lavalinkプロセスの起動 (java.exe を直接起動) ※Windows向け
-    let mut lavalink_child = {
-        #[cfg(windows)]
-        fn spawn_java_hidden() -> std::io::Result<tokio::process::Child> {
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-            let mut cmd = tokio::process::Command::new(
-                "D:/Programming/Rust/VH1-Bot/src/Lavalink/jdk-17/bin/java.exe",
+    // Lavalinkプロセスの起動と監視
+    let settings = config::global();
+    let lavalink_supervisor = match settings.lavalink.supervised_node() {
+        Some(node) if settings.lavalink.process.enabled => {
+            let supervisor = LavalinkSupervisor::spawn(
+                settings.lavalink.process.clone(),
+                node.clone(),
+                Arc::clone(&log_buffer),
+                Arc::clone(&pid_holder),
             );
-            cmd.args(["-jar", "Lavalink.jar"])
-                .current_dir("D:/Programming/Rust/VH1-Bot/src/Lavalink")
-                .stdout(std::process::Stdio::piped())
-                .stderr(std::process::Stdio::piped())
-                .creation_flags(CREATE_NO_WINDOW);
-            cmd.spawn()
-        }
-        spawn_java_hidden()
-    };
-
-    // LavalinkプロセスのPIDおよびログ読み取り設定
-    match &mut lavalink_child {
-        Ok(child) => {
-            // PID を取得して pid_holder にセット
-            if let Some(pid) = child.id() {
-                let mut holder = pid_holder.lock().unwrap();
-                *holder = Some(pid);
-                println!("[INFO] Lavalinkプロセスを起動しました。PID={}", pid);
-            } else {
-                println!("[WARN] LavalinkプロセスのPIDを取得できませんでした。");
-            }
-
-            // Lavalinkログを読み取り、log_buffer に貯める
-            if let Some(stdout) = child.stdout.take() {
-                let log_buffer_for_task = Arc::clone(&log_buffer);
-                tokio::spawn(async move {
-                    use tokio::io::{AsyncBufReadExt, BufReader};
-                    let mut reader = BufReader::new(stdout);
-                    let mut line = String::new();
-
-                    while let Ok(bytes_read) = reader.read_line(&mut line).await {
-                        if bytes_read == 0 {
-                            // EOF (子プロセス終了など)
-                            break;
-                        }
-                        {
-                            let mut buf = log_buffer_for_task.lock().unwrap();
-                            buf.push(line.trim_end().to_string());
-                            if buf.len() > 1000 {
-                                buf.remove(0);
-                            }
-                        }
-                        append_log("logs/lavalink.log", line.trim_end()); // ★追加
-                        line.clear();
-                    }
-                });
+            let timeout = Duration::from_secs(settings.lavalink.process.ready_timeout_secs);
+            if !supervisor.wait_ready(timeout).await {
+                tracing::warn!(
+                    "Lavalinkが{}秒以内に応答しませんでした。接続を続行します。",
+                    timeout.as_secs()
+                );
             }
+            Some(supervisor)
         }
-        Err(err) => eprintln!("Lavalinkプロセスの起動に失敗しました: {:?}", err),
-    }
+        _ => None,
+    };
+
+    // 設定ファイルの変更を監視して自動で再読み込みする
+    let config_watcher = config::spawn_watcher(CONFIG_WATCH_INTERVAL);
+    // 翻訳サービスの使用量の監視
+    let usage_monitor = translation::spawn_usage_monitor(Arc::clone(&client.http));
 
     // shutdown シグナル待ちと Discord Client の起動を並行処理
-    tokio::select! {
+    let result = tokio::select! {
         res = client.start() => {
-            if let Err(err) = res {
-                eprintln!("Client error: {:?}", err);
-                return Err(err.into());
+            if let Err(err) = &res {
+                tracing::error!("Client error: {:?}", err);
             }
+            res.map_err(Into::into)
         },
         _ = &mut shutdown_rx => {
-            println!("停止要求を受信しました。");
-            // Discord Client停止
-            client.shard_manager.shutdown_all().await;
-
-            // Lavalinkプロセス停止
-            if let Ok(child) = &mut lavalink_child {
-                if let Err(err) = child.kill().await {
-                    eprintln!("Lavalinkプロセスの停止に失敗しました: {:?}", err);
-                } else {
-                    println!("Lavalinkプロセスに終了要求を送りました。");
-                    match child.wait().await {
-                        Ok(status) => println!("Lavalinkプロセスが終了しました。終了コード: {:?}", status.code()),
-                        Err(err) => eprintln!("Lavalinkプロセスの終了待機に失敗しました: {:?}", err),
-                    }
-                }
-            }
+            tracing::info!("停止要求を受信しました。");
+            Ok(())
         },
-    }
-    Ok(())
+    };
+
+    // どちらの場合も停止処理を一括で行う
+    config_watcher.abort();
+    usage_monitor.abort();
+    // 監視タスクで書き出していない翻訳文字数の集計を保存する
+    let _ = tokio::task::spawn_blocking(|| translation::usage_tracker().flush()).await;
+    ShutdownCoordinator::new(log_buffer)
+        .run(ShutdownTargets {
+            shard_manager: Arc::clone(&client.shard_manager),
+            songbird,
+            lavalink: lavalink_slot.get().cloned(),
+            supervisor: lavalink_supervisor,
+        })
+        .await;
+    result
 }
 
 // ------------------------------- GUI用構造体 -------------------------------
+/// ウィンドウを閉じたときに停止処理の完了を待つ最大時間
+const GUI_SHUTDOWN_WAIT: Duration = Duration::from_secs(30);
+
 struct MyEguiApp {
     /// Botが現在起動中かどうか
     bot_running: Arc<AtomicBool>,
@@ -392,6 +785,13 @@ struct MyEguiApp {
     lavalink_logs: Arc<Mutex<Vec<String>>>,
     lavalink_pid: Arc<Mutex<Option<u32>>>,
     chat_messages: Arc<Mutex<Vec<String>>>,
+    /// 設定の再読み込み結果
+    reload_status: Option<Result<String, String>>,
+    /// 保存済みメッセージの検索語・検索する日数と結果
+    archive_search: String,
+    archive_days: u32,
+    archive_results: Arc<Mutex<Vec<String>>>,
+    archive_searching: Arc<AtomicBool>,
 }
 
 impl MyEguiApp {
@@ -405,6 +805,11 @@ impl MyEguiApp {
             lavalink_logs: Arc::new(Mutex::new(Vec::new())),
             lavalink_pid: Arc::new(Mutex::new(None)),
             chat_messages: Arc::new(Mutex::new(Vec::new())), // ★ 初期化
+            reload_status: None,
+            archive_search: String::new(),
+            archive_days: 7,
+            archive_results: Arc::new(Mutex::new(Vec::new())),
+            archive_searching: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -479,47 +884,135 @@ impl App for MyEguiApp {
                         if let Err(e) =
                             run_bot(shutdown_rx, lavalink_logs, pid_holder, chat_message).await
                         {
-                            eprintln!("Bot error: {:?}", e);
+                            tracing::error!("Bot error: {:?}", e);
                         }
                         bot_flag.store(false, Ordering::SeqCst);
                     });
                     self.runtime = Some(rt);
                 }
+            } else if self.shutdown_tx.is_none() {
+                // 停止要求を送った後、停止処理が終わるまで
+                ui.label("停止処理中... (進捗はログ欄を参照)");
+                ui.add_enabled(false, egui::Button::new("Botを停止"));
             } else {
                 // 起動中
                 ui.label("Botは起動中");
+                if let Some(pid) = *self.lavalink_pid.lock().unwrap() {
+                    ui.label(format!("Lavalink PID: {}", pid));
+                }
                 if ui.button("Botを停止").clicked() {
-                    // 1) シャットダウン通知
+                    // 停止処理は run_bot 側の ShutdownCoordinator が行う
                     if let Some(tx) = self.shutdown_tx.take() {
                         let _ = tx.send(());
                     }
-                    // 2) Tokioランタイムを閉じる
-                    if let Some(rt) = self.runtime.take() {
-                        rt.shutdown_background();
+                }
+            };
+            // 設定の再読み込み (Bot の起動状態に関わらず可能)
+            if ui.button("設定を再読み込み").clicked() {
+                self.reload_status = Some(config::reload_and_report());
+            }
+            match &self.reload_status {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(message)) => {
+                    ui.colored_label(egui::Color32::RED, message);
+                }
+                None => {}
+            }
+            let stats = translation::cache().stats();
+            ui.label(format!(
+                "翻訳キャッシュ: ヒット {} / ミス {} ({:.1}%) 保持 {}/{}件",
+                stats.hits,
+                stats.misses,
+                stats.hit_rate(),
+                stats.entries,
+                stats.capacity
+            ));
+            ui.collapsing("翻訳の使用量", |ui| {
+                let tracker = translation::usage_tracker();
+                match tracker.last_usage() {
+                    Some((checked_at, usage)) => {
+                        ui.label(format!(
+                            "{} / {} 文字 ({:.1}%) {} 時点",
+                            usage.character_count,
+                            usage.character_limit,
+                            usage.percent().unwrap_or(0.0),
+                            checked_at.format("%m/%d %H:%M")
+                        ));
+                    }
+                    None => {
+                        ui.label("未取得 (Bot の起動後に取得します)");
                     }
-                    // 3) Lavalinkプロセスを強制終了
-                    if let Some(pid) = *self.lavalink_pid.lock().unwrap() {
-                        let output = SysCommand::new("taskkill")
-                            .args(["/F", "/PID", &pid.to_string()])
-                            .output();
-                        match output {
-                            Ok(o) => {
-                                println!(
-                                    "[INFO] taskkill output: {}",
-                                    String::from_utf8_lossy(&o.stdout)
-                                );
-                            }
-                            Err(err) => {
-                                eprintln!("taskkillエラー: {:?}", err);
+                }
+                ui.label("サーバー別 (今月):");
+                for (guild_id, characters) in tracker.top_guilds(5) {
+                    ui.label(format!("  {}: {} 文字", guild_id, characters));
+                }
+                ui.label("チャンネル別 (今月):");
+                for (channel_id, characters) in tracker.top_channels(None, 5) {
+                    ui.label(format!("  {}: {} 文字", channel_id, characters));
+                }
+            });
+            ui.collapsing("メッセージ検索", |ui| {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.archive_search);
+                    ui.add(
+                        egui::DragValue::new(&mut self.archive_days)
+                            .range(1..=365)
+                            .suffix(" 日前まで"),
+                    );
+                    let searching = self.archive_searching.load(Ordering::SeqCst);
+                    if ui
+                        .add_enabled(!searching, egui::Button::new("検索"))
+                        .clicked()
+                    {
+                        let contains = self.archive_search.trim();
+                        let query = ArchiveQuery {
+                            contains: (!contains.is_empty()).then(|| contains.to_string()),
+                            since: Some(
+                                chrono::Utc::now() - chrono::Days::new(self.archive_days.into()),
+                            ),
+                            limit: 50,
+                            ..Default::default()
+                        };
+                        // ファイルの読み込みで画面が固まらないよう別スレッドで検索する
+                        let results = Arc::clone(&self.archive_results);
+                        let searching = Arc::clone(&self.archive_searching);
+                        searching.store(true, Ordering::SeqCst);
+                        std::thread::spawn(move || {
+                            let mut lines: Vec<String> = archive::archive()
+                                .query(&query)
+                                .iter()
+                                .map(ArchivedMessage::summary)
+                                .collect();
+                            if lines.is_empty() {
+                                lines.push("見つかりませんでした".to_string());
                             }
-                        }
+                            *results.lock().unwrap() = lines;
+                            searching.store(false, Ordering::SeqCst);
+                        });
+                    }
+                    if searching {
+                        ui.spinner();
                     }
-                    // PIDをリセット
-                    *self.lavalink_pid.lock().unwrap() = None;
-                    // 起動フラグを下ろす
-                    self.bot_running.store(false, Ordering::SeqCst);
+                });
+                egui::ScrollArea::vertical()
+                    .id_salt("archive_results")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for line in self.archive_results.lock().unwrap().iter() {
+                            ui.label(line);
+                        }
+                    });
+            });
+            // 停止処理が終わったらランタイムを閉じる
+            if !self.bot_running.load(Ordering::SeqCst) {
+                if let Some(rt) = self.runtime.take() {
+                    rt.shutdown_background();
                 }
-            };
+                self.shutdown_tx = None;
+            }
             /*
                                制作途中
             */
@@ -558,32 +1051,81 @@ impl App for MyEguiApp {
 
 impl Drop for MyEguiApp {
     fn drop(&mut self) {
-        // もし Lavalinkプロセス が残っていれば強制終了
-
-        if let Some(pid) = *self.lavalink_pid.lock().unwrap() {
-            let output = SysCommand::new("taskkill")
-                .args(["/F", "/PID", &pid.to_string()])
-                .output();
-            match output {
-                Ok(o) => {
-                    println!(
-                        "[INFO] taskkill output: {}",
-                        String::from_utf8_lossy(&o.stdout)
-                    );
-                }
-                Err(err) => {
-                    eprintln!("taskkillエラー: {:?}", err);
-                }
+        // ウィンドウを閉じた場合も停止ボタンと同じ停止処理を行う
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+        let deadline = std::time::Instant::now() + GUI_SHUTDOWN_WAIT;
+        while self.bot_running.load(Ordering::SeqCst) && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        if let Some(rt) = self.runtime.take() {
+            // 残ったタスクは破棄される (Lavalinkプロセスは kill_on_drop で終了する)
+            rt.shutdown_timeout(Duration::from_secs(1));
+        }
+    }
+}
+
+// ------------------------------- コマンドライン引数 -------------------------------
+#[derive(Debug, Default)]
+struct CliArgs {
+    /// `--config <path>` で指定された設定ファイル
+    config: Option<PathBuf>,
+    /// `--headless` 指定時は GUI を使わずに起動する
+    headless: bool,
+}
+
+fn parse_args() -> CliArgs {
+    let mut args = CliArgs::default();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--config" {
+            match iter.next() {
+                Some(path) => args.config = Some(PathBuf::from(path)),
+                None => eprintln!("[WARN] --config にはパスを指定してください"),
             }
+        } else if arg == "--headless" {
+            args.headless = true;
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            args.config = Some(PathBuf::from(path));
+        } else {
+            eprintln!("[WARN] 不明な引数を無視します: {}", arg);
         }
     }
+    args
 }
 
 // ------------------------------- メインエントリーポイント -------------------------------
 fn main() {
-    let _ = tracing_subscriber::fmt::try_init();
+    let args = parse_args();
+    if !args.headless {
+        let _ = tracing_subscriber::fmt::try_init();
+    }
+
+    match config::load(args.config.as_deref()) {
+        Ok((path, data)) => {
+            match &path {
+                Some(path) => println!("[INFO] 設定ファイルを読み込みました: {}", path.display()),
+                None => println!("[INFO] 設定ファイルが無いため環境変数の設定を使用します"),
+            }
+            config::init(args.config.clone(), path, data);
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
 
     let _ = create_dir_all("logs"); // ★追加
+
+    if args.headless {
+        if let Err(err) = headless::run() {
+            eprintln!("Bot error: {:?}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let native_options = NativeOptions {
         vsync: true,
         // 好みに合わせてウィンドウサイズなどを設定
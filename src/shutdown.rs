@@ -0,0 +1,201 @@
+use lavalink_rs::model::track::TrackData;
+use lavalink_rs::prelude::*;
+use poise::serenity_prelude::ShardManager;
+use serde::Serialize;
+use songbird::Songbird;
+use std::fs::create_dir_all;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::commands::music::music_basic::PlayerState;
+use crate::supervisor::{push_log, LavalinkSupervisor};
+
+/// プレイヤーキューの保存先
+const PLAYER_QUEUES_PATH: &str = "data/player_queues.json";
+/// 各ステップの制限時間 (Lavalink プロセスの停止は除く)
+const STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 停止処理で片付ける対象
+pub struct ShutdownTargets {
+    pub shard_manager: Arc<ShardManager>,
+    pub songbird: Arc<Songbird>,
+    /// Bot が ready になる前に停止した場合は None
+    pub lavalink: Option<LavalinkClient>,
+    pub supervisor: Option<LavalinkSupervisor>,
+}
+
+/// 保存するプレイヤーの状態
+#[derive(Serialize)]
+struct SavedPlayer {
+    guild_id: u64,
+    state: PlayerState,
+    current: Option<TrackData>,
+    position_ms: u64,
+    queue: Vec<TrackData>,
+}
+
+/// 停止処理をまとめて行う
+///
+/// ボイスチャンネルからの退出、キューの保存、Lavalink セッションの終了、
+/// Discord 接続の切断、Lavalink プロセスの停止を順に行い、
+/// 各ステップの結果を GUI のログ欄とログに報告する。
+pub struct ShutdownCoordinator {
+    log_buffer: Arc<Mutex<Vec<String>>>,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(log_buffer: Arc<Mutex<Vec<String>>>) -> Self {
+        Self { log_buffer }
+    }
+
+    fn report(&self, step: &str, result: &Result<String, String>) {
+        let line = match result {
+            Ok(detail) => {
+                let line = format!("[shutdown] OK   {}: {}", step, detail);
+                tracing::info!("{}", line);
+                line
+            }
+            Err(detail) => {
+                let line = format!("[shutdown] FAIL {}: {}", step, detail);
+                tracing::warn!("{}", line);
+                line
+            }
+        };
+        push_log(&self.log_buffer, &line);
+    }
+
+    /// 制限時間付きでステップを実行し、結果を報告する
+    async fn step<F>(&self, step: &str, timeout: Duration, fut: F)
+    where
+        F: Future<Output = Result<String, String>>,
+    {
+        let result = match tokio::time::timeout(timeout, fut).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("{}秒以内に完了しませんでした", timeout.as_secs())),
+        };
+        self.report(step, &result);
+    }
+
+    pub async fn run(&self, targets: ShutdownTargets) {
+        tracing::info!("停止処理を開始します。");
+
+        if let Some(lavalink) = &targets.lavalink {
+            self.step("キューの保存", STEP_TIMEOUT, persist_queues(lavalink))
+                .await;
+            self.step(
+                "Lavalinkセッションの終了",
+                STEP_TIMEOUT,
+                close_players(lavalink),
+            )
+            .await;
+        }
+
+        self.step(
+            "ボイスチャンネルからの退出",
+            STEP_TIMEOUT,
+            leave_voice_channels(&targets.songbird),
+        )
+        .await;
+
+        self.step("Discordからの切断", STEP_TIMEOUT, async {
+            targets.shard_manager.shutdown_all().await;
+            Ok("全シャードを停止しました".to_string())
+        })
+        .await;
+
+        if let Some(supervisor) = targets.supervisor {
+            // プロセス側で猶予時間を管理するため、ここでは制限時間を設けない
+            let result = supervisor
+                .shutdown()
+                .await
+                .map(|outcome| outcome.to_string());
+            self.report("Lavalinkプロセスの停止", &result);
+        }
+
+        tracing::info!("停止処理が完了しました。");
+    }
+}
+
+async fn persist_queues(lavalink: &LavalinkClient) -> Result<String, String> {
+    let contexts: Vec<PlayerContext> = lavalink
+        .players
+        .iter()
+        .filter_map(|entry| entry.value().0.load_full())
+        .map(|context| (*context).clone())
+        .collect();
+
+    let mut saved = Vec::new();
+    let mut failed = Vec::new();
+    for context in contexts {
+        let Ok(data) = context.data::<tokio::sync::Mutex<PlayerState>>() else {
+            continue;
+        };
+        let state = data.lock().await.clone();
+        // 1つのギルドで失敗しても他のギルドのキューは保存する
+        let player = match context.get_player().await {
+            Ok(player) => player,
+            Err(err) => {
+                failed.push(format!("{}: {}", context.guild_id.0, err));
+                continue;
+            }
+        };
+        let queue = match context.get_queue().get_queue().await {
+            Ok(queue) => queue,
+            Err(err) => {
+                failed.push(format!("{}: {}", context.guild_id.0, err));
+                continue;
+            }
+        };
+        saved.push(SavedPlayer {
+            guild_id: context.guild_id.0,
+            state,
+            current: player.track,
+            position_ms: player.state.position,
+            queue: queue.into_iter().map(|track| track.track).collect(),
+        });
+    }
+
+    if let Some(parent) = std::path::Path::new(PLAYER_QUEUES_PATH).parent() {
+        create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&saved).map_err(|err| err.to_string())?;
+    std::fs::write(PLAYER_QUEUES_PATH, json).map_err(|err| err.to_string())?;
+    let message = format!(
+        "{}件のプレイヤーを {} に保存しました",
+        saved.len(),
+        PLAYER_QUEUES_PATH
+    );
+    if failed.is_empty() {
+        Ok(message)
+    } else {
+        Err(format!("{} (失敗: {})", message, failed.join(", ")))
+    }
+}
+
+async fn close_players(lavalink: &LavalinkClient) -> Result<String, String> {
+    let count = lavalink.players.len();
+    lavalink
+        .delete_all_player_contexts()
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(format!("{}件のプレイヤーを終了しました", count))
+}
+
+async fn leave_voice_channels(songbird: &Songbird) -> Result<String, String> {
+    let guild_ids: Vec<_> = songbird.iter().map(|(guild_id, _)| guild_id).collect();
+    let mut failed = Vec::new();
+    for guild_id in &guild_ids {
+        if let Err(err) = songbird.remove(*guild_id).await {
+            failed.push(format!("{}: {}", guild_id, err));
+        }
+    }
+    if failed.is_empty() {
+        Ok(format!(
+            "{}件のボイスチャンネルから退出しました",
+            guild_ids.len()
+        ))
+    } else {
+        Err(failed.join(", "))
+    }
+}
@@ -1,13 +1,18 @@
+use futures::future::join_all;
+use poise::serenity_prelude::AutocompleteChoice;
 use poise::serenity_prelude::Color;
 use poise::serenity_prelude::CreateEmbed;
 use poise::serenity_prelude::Message;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
 
+use crate::error_handler::UserError;
+use crate::translation::{
+    self, CacheKey, GlossaryChoice, Language, Origin, TranslateError, TranslateOptions,
+};
 use crate::Context;
+use crate::Data;
 use crate::Error;
-use crate::TranslationResponse;
-use crate::GLOBAL_DATA;
 
 #[poise::command(slash_command, prefix_command)]
 pub async fn ping(ctx: Context<'_>) -> Result<(), Error> {
@@ -16,56 +21,284 @@ pub async fn ping(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-#[poise::command(slash_command, prefix_command)]
-pub async fn trans(ctx: Context<'_>, language: String, word: Vec<String>) -> Result<(), Error> {
-    let text_to_translate = word.join(" ");
-    let translate_language = language;
+/// 訳文の文体
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum Formality {
+    #[name = "formal"]
+    More,
+    #[name = "informal"]
+    Less,
+}
 
-    let trans = translate(text_to_translate.as_str(), translate_language.as_str()).await;
+impl Formality {
+    /// DeepL の formality の値 (対応しない言語ではエラーにせず無視される形式)
+    fn as_deepl(self) -> &'static str {
+        match self {
+            Formality::More => "prefer_more",
+            Formality::Less => "prefer_less",
+        }
+    }
+}
 
-    if !ctx.author().bot {
-        let embed = CreateEmbed::new()
-            .title(&ctx.author().name)
-            .color(Color::DARK_BLUE)
-            .description(format!("`{}`: {}", trans.0, trans.1));
+/// 補完候補の最大数 (Discord の上限)
+const AUTOCOMPLETE_LIMIT: usize = 25;
 
-        let reply = poise::CreateReply::default().reply(true).embed(embed);
+/// 翻訳先言語の補完 (カンマ区切りの最後の要素を補完する)
+pub async fn autocomplete_language(_ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let (chosen, current) = match partial.rsplit_once(',') {
+        Some((chosen, current)) => (format!("{},", chosen), current.trim().to_lowercase()),
+        None => (String::new(), partial.trim().to_lowercase()),
+    };
+    translation::target_languages()
+        .await
+        .iter()
+        .filter(|language| {
+            language.code.to_lowercase().starts_with(&current)
+                || language.name.to_lowercase().contains(&current)
+        })
+        .take(AUTOCOMPLETE_LIMIT)
+        .map(|language| {
+            AutocompleteChoice::new(
+                format!("{} ({})", language.name, language.code),
+                format!("{}{}", chosen, language.code),
+            )
+        })
+        .collect()
+}
+
+/// 対応していない言語が指定されたときのエラー (使える言語の一覧を添える)
+fn unsupported_languages(languages: &[Language], unknown: &[&str]) -> UserError {
+    let codes = languages
+        .iter()
+        .map(|language| language.code.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    UserError::new(
+        format!(
+            "対応していない言語です: {}\n使える言語: {}",
+            unknown.join(", "),
+            codes
+        ),
+        format!(
+            "Unsupported language: {}\nAvailable languages: {}",
+            unknown.join(", "),
+            codes
+        ),
+    )
+}
 
-        ctx.send(reply).await?;
+/// カンマや空白で区切られた言語コードを対応言語の一覧と照合する
+///
+/// 重複は取り除き、1つでも対応していないコードがあればエラーにする。
+pub fn resolve_languages<'a>(
+    languages: &'a [Language],
+    input: &str,
+) -> Result<Vec<&'a Language>, UserError> {
+    let mut targets: Vec<&Language> = Vec::new();
+    let mut unknown = Vec::new();
+    for code in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|code| !code.is_empty())
+    {
+        match translation::find_language(languages, code) {
+            Some(language) if !targets.iter().any(|t| t.code == language.code) => {
+                targets.push(language)
+            }
+            Some(_) => {}
+            None => unknown.push(code),
+        }
     }
+    if !unknown.is_empty() || targets.is_empty() {
+        return Err(unsupported_languages(languages, &unknown));
+    }
+    Ok(targets)
+}
 
-    Ok(())
+/// 言語コードを1つだけ受け付けて対応言語の一覧と照合する
+pub fn resolve_language<'a>(
+    languages: &'a [Language],
+    input: &str,
+) -> Result<&'a Language, UserError> {
+    let input = input.trim();
+    translation::find_language(languages, input)
+        .ok_or_else(|| unsupported_languages(languages, &[input]))
+}
+
+/// `/trans` (スラッシュコマンド) に `s!trans <言語> <文章>` の形式を組み合わせたコマンド
+///
+/// プレフィックスコマンドでは `#[rest]` を最後の引数にする必要があり、スラッシュコマンドでは
+/// 省略可能な引数を後ろに置く必要があるため、それぞれの関数を1つのコマンドにまとめる。
+pub fn trans_command() -> poise::Command<Data, Error> {
+    let mut command = trans();
+    command.prefix_action = trans_prefix().prefix_action;
+    command
+}
+
+/// Translate text into one or more languages.
+#[poise::command(slash_command)]
+pub async fn trans(
+    ctx: Context<'_>,
+    #[description = "Target languages, comma separated (e.g. EN-US,JA)"]
+    #[autocomplete = "autocomplete_language"]
+    language: String,
+    #[description = "Text to translate"] text: String,
+    #[description = "Formality (only for languages that support it)"] formality: Option<Formality>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<(), Error> {
+    run_trans(ctx, language, text, formality, ephemeral.unwrap_or(false)).await
 }
 
-pub async fn translate(text_to_translate: &str, translate_language: &str) -> (String, String) {
-    let client = reqwest::Client::new();
-    let response = client
-        .post(GLOBAL_DATA.endpoint.api_endpoint.as_str())
-        .header(
-            "Authorization",
-            format!("DeepL-Auth-Key {}", GLOBAL_DATA.token.api_key),
+/// `s!trans <言語> <文章>`
+#[poise::command(prefix_command)]
+pub async fn trans_prefix(
+    ctx: Context<'_>,
+    language: String,
+    #[rest] text: String,
+) -> Result<(), Error> {
+    run_trans(ctx, language, text, None, false).await
+}
+
+async fn run_trans(
+    ctx: Context<'_>,
+    language: String,
+    text: String,
+    formality: Option<Formality>,
+    ephemeral: bool,
+) -> Result<(), Error> {
+    if ctx.author().bot {
+        return Ok(());
+    }
+    if text.trim().is_empty() {
+        return Err(UserError::new(
+            "翻訳する文章を入力してください。",
+            "Please enter the text to translate.",
         )
-        .header("Content-Type", "application/json")
-        .json(&serde_json::json!({
-            "text": [text_to_translate.replace("\"", "\\\"")],
-            "target_lang": translate_language
-        }))
-        .send()
-        .await
-        .unwrap()
-        .json::<TranslationResponse>()
-        .await
-        .unwrap();
-
-    let trans = {
-        let trans = response.translations[0].text.trim().to_string();
-        let language = response.translations[0]
-            .detected_source_language
-            .trim()
-            .to_string();
-        (language, trans)
+        .into());
+    }
+
+    // 指定された言語を対応言語の一覧と照合する
+    let languages = translation::target_languages().await;
+    let targets = resolve_languages(&languages, &language)?;
+
+    if ephemeral {
+        ctx.defer_ephemeral().await?;
+    } else {
+        ctx.defer().await?;
+    }
+
+    let origin = Origin {
+        guild_id: ctx.guild_id(),
+        channel_id: ctx.channel_id(),
+    };
+    let guild_settings = ctx
+        .guild_id()
+        .map(|guild_id| ctx.data().guild_settings.get(guild_id))
+        .unwrap_or_default();
+    let backend_name = translation::current_backend().name();
+    let results = join_all(targets.iter().map(|target| {
+        let options = TranslateOptions {
+            formality: formality
+                .filter(|_| target.supports_formality)
+                .map(Formality::as_deepl),
+            ..Default::default()
+        };
+        let glossaries = guild_settings.glossaries_for(&target.code, backend_name);
+        let text = text.as_str();
+        async move { translate(text, &target.code, origin, &options, &glossaries).await }
+    }))
+    .await;
+
+    let mut description = String::new();
+    for (target, result) in targets.iter().zip(results) {
+        let (source_lang, translated) = result.map_err(TranslateError::into_command_error)?;
+        description.push_str(&format!(
+            "**{}** (`{}` → `{}`)\n{}\n",
+            target.name, source_lang, target.code, translated
+        ));
+    }
+
+    let embed = CreateEmbed::new()
+        .title(&ctx.author().name)
+        .color(Color::DARK_BLUE)
+        .description(description);
+    let reply = poise::CreateReply::default()
+        .reply(true)
+        .embed(embed)
+        .ephemeral(ephemeral);
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// 設定で選ばれた翻訳バックエンドで翻訳し、(検出言語, 訳文) を返す
+///
+/// 同じ文章の翻訳結果はキャッシュから返し、翻訳サービスに送った文字数は `origin` ごとに集計する。
+/// レート制限や一時的な通信エラーは数回まで再試行する。
+///
+/// 用語集は原文の言語を指定しないと使えない。翻訳先の用語集が1つだけなら原文をその言語とみなし、
+/// 用語集を使って1回で翻訳する。複数あればまず用語集なしで翻訳して言語を検出し、
+/// その言語の用語集があるときだけ用語集を使って翻訳し直す (文字数も2回分かかる)。
+pub async fn translate(
+    text_to_translate: &str,
+    translate_language: &str,
+    origin: Origin,
+    options: &TranslateOptions<'_>,
+    glossaries: &[GlossaryChoice<'_>],
+) -> Result<(String, String), TranslateError> {
+    if let [glossary] = glossaries {
+        let options = options.with_glossary(*glossary);
+        return translate_cached(text_to_translate, translate_language, origin, &options).await;
+    }
+    let (source_lang, text) =
+        translate_cached(text_to_translate, translate_language, origin, options).await?;
+    if translation::same_language(&source_lang, translate_language) {
+        return Ok((source_lang, text));
+    }
+    let Some(glossary) = glossaries
+        .iter()
+        .find(|glossary| translation::same_language(&source_lang, glossary.source_lang))
+    else {
+        return Ok((source_lang, text));
+    };
+    let options = options.with_glossary(*glossary);
+    translate_cached(text_to_translate, translate_language, origin, &options).await
+}
+
+async fn translate_cached(
+    text_to_translate: &str,
+    translate_language: &str,
+    origin: Origin,
+    options: &TranslateOptions<'_>,
+) -> Result<(String, String), TranslateError> {
+    let backend = translation::current_backend();
+    // メンションやコードなどが崩れないよう保護してから送る
+    let protected = translation::protect(text_to_translate);
+    // キャッシュには保護した形のまま保存し、元に戻すのはリクエストごとの内容で行う
+    // (空白の違いなどでキーが同じになっても、コードブロックなどは常にこの原文のものになる)
+    let key = CacheKey::new(
+        backend.name(),
+        &protected.markup,
+        translate_language,
+        options.formality,
+        options.glossary_id,
+    );
+    let translation = match translation::cache().get(&key) {
+        Some(translation) => translation,
+        None => {
+            let translation = translation::translate_with_retry(
+                backend.as_ref(),
+                &protected.markup,
+                translate_language,
+                options,
+            )
+            .await?;
+            translation::usage_tracker().record(origin, text_to_translate.chars().count());
+            translation::cache().insert(key, translation.clone());
+            translation
+        }
     };
-    trans
+    let text = protected.restore(&translation.text);
+    Ok((translation.detected_source_language, text))
 }
 
 /// ログメッセージを非同期でファイルに出力するヘルパー関数
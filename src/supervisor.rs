@@ -0,0 +1,296 @@
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::config::{LavalinkNode, LavalinkProcess};
+use crate::logging::append_log;
+
+/// 再起動待機時間の初期値
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// この時間以上動いていたら安定稼働とみなしてバックオフをリセットする
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+/// ヘルスチェックの間隔
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// GUI 用ログバッファの最大行数
+const LOG_BUFFER_LIMIT: usize = 1000;
+
+/// Lavalink プロセスの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LavalinkState {
+    Starting,
+    Ready,
+    Restarting,
+    Stopped,
+}
+
+/// Lavalink プロセスの監視役
+///
+/// 標準出力と標準エラーをログに流し、`/version` が応答するまでを起動中とみなす。
+/// プロセスが落ちた場合は指数バックオフで再起動する。
+pub struct LavalinkSupervisor {
+    state_rx: watch::Receiver<LavalinkState>,
+    stop_tx: watch::Sender<bool>,
+    task: JoinHandle<std::io::Result<StopOutcome>>,
+}
+
+/// 停止時に Lavalink プロセスがどう終了したか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopOutcome {
+    /// 停止要求の時点でプロセスが動いていなかった
+    NotRunning,
+    /// 終了要求に応じて自ら終了した
+    Exited,
+    /// 猶予時間内に終了しなかったため強制終了した
+    Killed,
+}
+
+impl std::fmt::Display for StopOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopOutcome::NotRunning => write!(f, "プロセスは起動していませんでした"),
+            StopOutcome::Exited => write!(f, "プロセスは正常に終了しました"),
+            StopOutcome::Killed => write!(f, "猶予時間内に終了しなかったため強制終了しました"),
+        }
+    }
+}
+
+impl LavalinkSupervisor {
+    /// 監視タスクを起動する
+    pub fn spawn(
+        process: LavalinkProcess,
+        node: LavalinkNode,
+        log_buffer: Arc<Mutex<Vec<String>>>,
+        pid_holder: Arc<Mutex<Option<u32>>>,
+    ) -> Self {
+        let (state_tx, state_rx) = watch::channel(LavalinkState::Starting);
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let task = tokio::spawn(supervise(
+            process, node, log_buffer, pid_holder, state_tx, stop_rx,
+        ));
+        Self {
+            state_rx,
+            stop_tx,
+            task,
+        }
+    }
+
+    /// `/version` が応答するまで待つ。タイムアウトした場合は false
+    pub async fn wait_ready(&self, timeout: Duration) -> bool {
+        let mut state_rx = self.state_rx.clone();
+        tokio::time::timeout(
+            timeout,
+            state_rx.wait_for(|state| *state == LavalinkState::Ready),
+        )
+        .await
+        .is_ok_and(|res| res.is_ok())
+    }
+
+    /// 再起動を止めてプロセスを終了させる
+    ///
+    /// まず終了要求を送り、`shutdown_timeout_secs` 以内に終了しなければ強制終了する。
+    pub async fn shutdown(self) -> Result<StopOutcome, String> {
+        let _ = self.stop_tx.send(true);
+        match self.task.await {
+            Ok(Ok(outcome)) => Ok(outcome),
+            Ok(Err(err)) => Err(format!("Lavalinkプロセスの停止に失敗しました: {}", err)),
+            Err(err) => Err(format!("Lavalink監視タスクの終了に失敗しました: {}", err)),
+        }
+    }
+}
+
+fn spawn_lavalink(process: &LavalinkProcess) -> std::io::Result<Child> {
+    let mut cmd = Command::new(&process.java);
+    cmd.args(&process.jvm_args)
+        .arg("-jar")
+        .arg(&process.jar)
+        .current_dir(&process.working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // コンソールウィンドウを表示しない
+    #[cfg(windows)]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    cmd.spawn()
+}
+
+/// プロセスに終了を依頼する (Unix では SIGTERM を送る)
+///
+/// Windows ではウィンドウを持たないプロセスに穏便な終了手段が無いため、
+/// 何もせずに false を返す。呼び出し側はその場合すぐに強制終了する。
+fn request_exit(child: &Child) -> bool {
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::Pid;
+
+        let Some(pid) = child.id().and_then(|pid| i32::try_from(pid).ok()) else {
+            return false;
+        };
+        kill(Pid::from_raw(pid), Signal::SIGTERM).is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = child;
+        false
+    }
+}
+
+/// 終了を依頼し、猶予時間を過ぎたら強制終了する
+async fn terminate(child: &mut Child, grace: Duration) -> std::io::Result<StopOutcome> {
+    if request_exit(child) {
+        if let Ok(status) = tokio::time::timeout(grace, child.wait()).await {
+            status?;
+            return Ok(StopOutcome::Exited);
+        }
+    }
+    child.kill().await?;
+    Ok(StopOutcome::Killed)
+}
+
+/// GUI 用バッファとログファイルに1行追加する
+pub(crate) fn push_log(log_buffer: &Mutex<Vec<String>>, line: &str) {
+    {
+        let mut buf = log_buffer.lock().unwrap();
+        buf.push(line.to_string());
+        if buf.len() > LOG_BUFFER_LIMIT {
+            buf.remove(0);
+        }
+    }
+    append_log("logs/lavalink.log", line);
+}
+
+/// 子プロセスの出力を1行ずつログに流す
+fn forward_output<R>(reader: R, prefix: &'static str, log_buffer: Arc<Mutex<Vec<String>>>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        while let Ok(bytes_read) = reader.read_line(&mut line).await {
+            if bytes_read == 0 {
+                // EOF (子プロセス終了など)
+                break;
+            }
+            push_log(&log_buffer, &format!("{}{}", prefix, line.trim_end()));
+            line.clear();
+        }
+    });
+}
+
+/// `/version` が 200 を返すまでポーリングする
+async fn wait_for_version(node: &LavalinkNode) {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .unwrap_or_default();
+    let url = format!("{}/version", node.base_url());
+    loop {
+        let res = client
+            .get(&url)
+            .header("Authorization", &node.password)
+            .send()
+            .await;
+        if let Ok(res) = res {
+            if res.status().is_success() {
+                let version = res.text().await.unwrap_or_default();
+                tracing::info!("Lavalinkの起動を確認しました。version={}", version);
+                return;
+            }
+        }
+        tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+    }
+}
+
+async fn supervise(
+    process: LavalinkProcess,
+    node: LavalinkNode,
+    log_buffer: Arc<Mutex<Vec<String>>>,
+    pid_holder: Arc<Mutex<Option<u32>>>,
+    state_tx: watch::Sender<LavalinkState>,
+    mut stop_rx: watch::Receiver<bool>,
+) -> std::io::Result<StopOutcome> {
+    let max_backoff = Duration::from_secs(process.max_backoff_secs).max(INITIAL_BACKOFF);
+    let shutdown_timeout = Duration::from_secs(process.shutdown_timeout_secs);
+    let mut backoff = INITIAL_BACKOFF;
+
+    while !*stop_rx.borrow() {
+        match spawn_lavalink(&process) {
+            Ok(mut child) => {
+                let started_at = Instant::now();
+                let pid = child.id();
+                *pid_holder.lock().unwrap() = pid;
+                match pid {
+                    Some(pid) => tracing::info!("Lavalinkプロセスを起動しました。PID={}", pid),
+                    None => tracing::warn!("LavalinkプロセスのPIDを取得できませんでした。"),
+                }
+
+                if let Some(stdout) = child.stdout.take() {
+                    forward_output(stdout, "", Arc::clone(&log_buffer));
+                }
+                if let Some(stderr) = child.stderr.take() {
+                    forward_output(stderr, "[stderr] ", Arc::clone(&log_buffer));
+                }
+
+                let health_check = wait_for_version(&node);
+                tokio::pin!(health_check);
+                let mut ready = false;
+
+                loop {
+                    tokio::select! {
+                        _ = &mut health_check, if !ready => {
+                            ready = true;
+                            let _ = state_tx.send(LavalinkState::Ready);
+                        }
+                        status = child.wait() => {
+                            match status {
+                                Ok(status) => tracing::warn!(
+                                    "Lavalinkプロセスが終了しました。終了コード: {:?}",
+                                    status.code()
+                                ),
+                                Err(err) => {
+                                    tracing::warn!("Lavalinkプロセスの終了待機に失敗しました: {:?}", err)
+                                }
+                            }
+                            break;
+                        }
+                        _ = stop_rx.changed() => {
+                            let outcome = terminate(&mut child, shutdown_timeout).await;
+                            *pid_holder.lock().unwrap() = None;
+                            let _ = state_tx.send(LavalinkState::Stopped);
+                            return outcome;
+                        }
+                    }
+                }
+
+                *pid_holder.lock().unwrap() = None;
+                if started_at.elapsed() >= STABLE_UPTIME {
+                    backoff = INITIAL_BACKOFF;
+                }
+            }
+            Err(err) => tracing::error!("Lavalinkプロセスの起動に失敗しました: {:?}", err),
+        }
+
+        let _ = state_tx.send(LavalinkState::Restarting);
+        tracing::info!("{}秒後にLavalinkを再起動します。", backoff.as_secs());
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = stop_rx.changed() => break,
+        }
+        backoff = (backoff * 2).min(max_backoff);
+        let _ = state_tx.send(LavalinkState::Starting);
+    }
+
+    let _ = state_tx.send(LavalinkState::Stopped);
+    Ok(StopOutcome::NotRunning)
+}
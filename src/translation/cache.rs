@@ -0,0 +1,262 @@
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use super::Translation;
+use crate::config::TranslationCacheSettings;
+
+/// キャッシュのキー
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// バックエンド名 (モックの結果を DeepL の結果として使わないため)
+    backend: String,
+    /// [`super::protect`] で保護した原文 (前後の空白を除き、連続する空白を1つにまとめる)
+    ///
+    /// コードブロックなどはプレースホルダーになっているため、その中の空白は区別しなくてよい。
+    text: String,
+    target_lang: String,
+    formality: Option<String>,
+    glossary_id: Option<String>,
+}
+
+impl CacheKey {
+    pub fn new(
+        backend: &str,
+        text: &str,
+        target_lang: &str,
+        formality: Option<&str>,
+        glossary_id: Option<&str>,
+    ) -> Self {
+        Self {
+            backend: backend.to_string(),
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            target_lang: target_lang.trim().to_uppercase(),
+            formality: formality.map(|formality| formality.to_lowercase()),
+            glossary_id: glossary_id.map(str::to_string),
+        }
+    }
+}
+
+/// ファイルに保存する1行分
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    key: CacheKey,
+    translation: Translation,
+}
+
+/// GUI やコマンドで表示する統計
+#[derive(Debug, Clone, Copy)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl CacheStats {
+    /// ヒット率 (%)
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+/// 翻訳結果のキャッシュ
+///
+/// 訳文は保護した形 (プレースホルダー入り) のまま保存する。
+/// メモリ上の LRU に加え、`persist` が有効なら JSONL ファイルに追記していき、
+/// 起動時にそこから読み戻す。
+pub struct TranslationCache {
+    /// capacity が 0 なら None (キャッシュ無効)
+    entries: Option<Mutex<LruCache<CacheKey, Translation>>>,
+    store: Option<PathBuf>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl TranslationCache {
+    pub fn new(settings: &TranslationCacheSettings) -> Self {
+        let entries = NonZeroUsize::new(settings.capacity).map(LruCache::new);
+        let store = match &entries {
+            Some(_) if settings.persist => Some(settings.path.clone()),
+            _ => None,
+        };
+        let cache = Self {
+            entries: entries.map(Mutex::new),
+            store,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        };
+        cache.load();
+        cache
+    }
+
+    /// 保存ファイルから読み戻す
+    fn load(&self) {
+        let (Some(entries), Some(path)) = (&self.entries, &self.store) else {
+            return;
+        };
+        let Ok(file) = File::open(path) else {
+            return;
+        };
+        let mut entries = entries.lock().unwrap();
+        let mut lines = 0;
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            lines += 1;
+            if let Ok(entry) = serde_json::from_str::<StoredEntry>(&line) {
+                entries.put(entry.key, entry.translation);
+            }
+        }
+        tracing::info!(
+            "翻訳キャッシュを {} から {}件読み込みました。",
+            path.display(),
+            entries.len()
+        );
+        // 追記し続けたファイルが膨らまないよう、保持している分だけで書き直す
+        if lines > entries.len() {
+            if let Err(err) = Self::rewrite(path, &entries) {
+                tracing::warn!(
+                    "翻訳キャッシュの書き直しに失敗しました ({}): {:?}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+
+    fn rewrite(
+        path: &Path,
+        entries: &LruCache<CacheKey, Translation>,
+    ) -> Result<(), std::io::Error> {
+        let tmp_path = path.with_extension("jsonl.tmp");
+        let mut file = File::create(&tmp_path)?;
+        // 古いものから順に書き、読み戻したときに LRU の順序が保たれるようにする
+        for (key, translation) in entries.iter().rev() {
+            let entry = StoredEntry {
+                key: key.clone(),
+                translation: translation.clone(),
+            };
+            writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        }
+        std::fs::rename(&tmp_path, path)
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<Translation> {
+        let found = self
+            .entries
+            .as_ref()
+            .and_then(|entries| entries.lock().unwrap().get(key).cloned());
+        match found {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        found
+    }
+
+    pub fn insert(&self, key: CacheKey, translation: Translation) {
+        let Some(entries) = &self.entries else {
+            return;
+        };
+        if let Some(path) = &self.store {
+            let entry = StoredEntry {
+                key: key.clone(),
+                translation: translation.clone(),
+            };
+            if let Err(err) = Self::append(path, &entry) {
+                tracing::warn!(
+                    "翻訳キャッシュの保存に失敗しました ({}): {:?}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+        entries.lock().unwrap().put(key, translation);
+    }
+
+    fn append(path: &Path, entry: &StoredEntry) -> Result<(), std::io::Error> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)
+    }
+
+    /// キャッシュと保存ファイルを空にする (統計はそのまま)
+    pub fn clear(&self) {
+        if let Some(entries) = &self.entries {
+            entries.lock().unwrap().clear();
+        }
+        if let Some(path) = &self.store {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let (entries, capacity) = match &self.entries {
+            Some(entries) => {
+                let entries = entries.lock().unwrap();
+                (entries.len(), entries.cap().get())
+            }
+            None => (0, 0),
+        };
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries,
+            capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_normalizes_whitespace_and_case() {
+        assert_eq!(
+            CacheKey::new("DeepL", "  hello \n  world ", "ja ", Some("More"), None),
+            CacheKey::new("DeepL", "hello world", "JA", Some("more"), None)
+        );
+    }
+
+    #[test]
+    fn key_distinguishes_backend_and_options() {
+        let key = CacheKey::new("DeepL", "hello", "JA", None, None);
+        assert_ne!(key, CacheKey::new("Mock", "hello", "JA", None, None));
+        assert_ne!(key, CacheKey::new("DeepL", "Hello", "JA", None, None));
+        assert_ne!(
+            key,
+            CacheKey::new("DeepL", "hello", "JA", Some("less"), None)
+        );
+        assert_ne!(key, CacheKey::new("DeepL", "hello", "JA", None, Some("g1")));
+    }
+
+    #[test]
+    fn hits_and_misses_are_counted() {
+        let cache = TranslationCache::new(&TranslationCacheSettings {
+            capacity: 10,
+            persist: false,
+            ..Default::default()
+        });
+        let key = CacheKey::new("Mock", "hello", "JA", None, None);
+        let translation = Translation {
+            detected_source_language: "EN".to_string(),
+            text: "こんにちは".to_string(),
+        };
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key.clone(), translation.clone());
+        assert_eq!(cache.get(&key), Some(translation));
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    }
+}
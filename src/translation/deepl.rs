@@ -0,0 +1,205 @@
+use poise::serenity_prelude::async_trait;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use super::{Language, TranslateError, TranslateOptions, Translation, TranslationBackend, Usage};
+use crate::config::{Database, DeeplPlan};
+
+const FREE_ENDPOINT: &str = "https://api-free.deepl.com/v2/translate";
+const PRO_ENDPOINT: &str = "https://api.deepl.com/v2/translate";
+
+#[derive(Deserialize, Debug)]
+struct Translations {
+    detected_source_language: String,
+    text: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TranslationResponse {
+    translations: Vec<Translations>,
+}
+
+#[derive(Deserialize, Debug)]
+struct UsageResponse {
+    character_count: u64,
+    character_limit: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct GlossaryResponse {
+    glossary_id: String,
+}
+
+/// DeepL API (Free / Pro)
+pub struct DeeplBackend {
+    client: reqwest::Client,
+    api_key: String,
+    endpoint: String,
+}
+
+impl DeeplBackend {
+    pub fn new(client: reqwest::Client, api_key: String, endpoint: String) -> Self {
+        Self {
+            client,
+            api_key,
+            endpoint,
+        }
+    }
+
+    /// 翻訳以外の API の URL (`endpoint` の `/translate` を置き換える)
+    fn api_url(&self, path: &str) -> String {
+        let base = self.endpoint.trim_end_matches('/');
+        let base = base.strip_suffix("/translate").unwrap_or(base);
+        format!("{}/{}", base, path)
+    }
+
+    /// 設定から生成する。`endpoint.api_endpoint` が空ならプランから接続先を決める
+    pub fn from_settings(client: reqwest::Client, settings: &Database) -> Self {
+        let api_key = settings.token.api_key.clone();
+        let endpoint = match settings.endpoint.api_endpoint.trim() {
+            "" => endpoint_for_plan(settings.translation.deepl_plan, &api_key).to_string(),
+            endpoint => endpoint.to_string(),
+        };
+        Self::new(client, api_key, endpoint)
+    }
+}
+
+fn endpoint_for_plan(plan: DeeplPlan, api_key: &str) -> &'static str {
+    match plan {
+        DeeplPlan::Free => FREE_ENDPOINT,
+        DeeplPlan::Pro => PRO_ENDPOINT,
+        // Free プランのキーは末尾が ":fx"
+        DeeplPlan::Auto if api_key.ends_with(":fx") => FREE_ENDPOINT,
+        DeeplPlan::Auto => PRO_ENDPOINT,
+    }
+}
+
+#[async_trait]
+impl TranslationBackend for DeeplBackend {
+    fn name(&self) -> &'static str {
+        "DeepL"
+    }
+
+    async fn translate(
+        &self,
+        text: &str,
+        target_lang: &str,
+        options: &TranslateOptions<'_>,
+    ) -> Result<Translation, TranslateError> {
+        let mut body = serde_json::json!({
+            "text": [text],
+            "target_lang": target_lang,
+            "tag_handling": "xml"
+        });
+        if let Some(source_lang) = options.source_lang {
+            body["source_lang"] = source_lang.into();
+        }
+        if let Some(glossary_id) = options.glossary_id {
+            body["glossary_id"] = glossary_id.into();
+        }
+        if let Some(formality) = options.formality {
+            body["formality"] = formality.into();
+        }
+        let response = self
+            .client
+            .post(self.endpoint.as_str())
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await?;
+        if let Some(err) = TranslateError::from_response(&response) {
+            return Err(err);
+        }
+        let response = response.json::<TranslationResponse>().await?;
+
+        let first = response.translations.into_iter().next().ok_or_else(|| {
+            TranslateError::Transient("DeepL のレスポンスに翻訳結果が含まれていません".to_string())
+        })?;
+        Ok(Translation {
+            detected_source_language: first.detected_source_language.trim().to_string(),
+            text: first.text.trim().to_string(),
+        })
+    }
+
+    async fn usage(&self) -> Result<Option<Usage>, TranslateError> {
+        let response = self
+            .client
+            .get(self.api_url("usage"))
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .send()
+            .await?;
+        if let Some(err) = TranslateError::from_response(&response) {
+            return Err(err);
+        }
+        let response = response.json::<UsageResponse>().await?;
+        Ok(Some(Usage {
+            character_count: response.character_count,
+            character_limit: response.character_limit,
+        }))
+    }
+
+    async fn target_languages(&self) -> Result<Option<Vec<Language>>, TranslateError> {
+        let response = self
+            .client
+            .get(self.api_url("languages"))
+            .query(&[("type", "target")])
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .send()
+            .await?;
+        if let Some(err) = TranslateError::from_response(&response) {
+            return Err(err);
+        }
+        Ok(Some(response.json::<Vec<Language>>().await?))
+    }
+
+    async fn create_glossary(
+        &self,
+        name: &str,
+        source_lang: &str,
+        target_lang: &str,
+        entries: &BTreeMap<String, String>,
+    ) -> Result<Option<String>, TranslateError> {
+        // DeepL の用語集は TSV 形式 (1行に「用語<TAB>訳語」)
+        let tsv = entries
+            .iter()
+            .map(|(term, translation)| format!("{}\t{}", term, translation))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let response = self
+            .client
+            .post(self.api_url("glossaries"))
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .json(&serde_json::json!({
+                "name": name,
+                "source_lang": source_lang,
+                "target_lang": target_lang,
+                "entries": tsv,
+                "entries_format": "tsv"
+            }))
+            .send()
+            .await?;
+        if let Some(err) = TranslateError::from_response(&response) {
+            return Err(err);
+        }
+        let response = response.json::<GlossaryResponse>().await?;
+        Ok(Some(response.glossary_id))
+    }
+
+    async fn delete_glossary(&self, glossary_id: &str) -> Result<(), TranslateError> {
+        let response = self
+            .client
+            .delete(self.api_url(&format!("glossaries/{}", glossary_id)))
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .send()
+            .await?;
+        // 既に削除されている場合は成功とみなす
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        match TranslateError::from_response(&response) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::error_handler::UserError;
+
+/// 翻訳の失敗理由
+#[derive(Debug)]
+pub enum TranslateError {
+    /// 文字数の上限に達した (DeepL の 456)
+    QuotaExceeded,
+    /// リクエストが多すぎる (429)。`retry_after` は Retry-After ヘッダの値
+    RateLimited { retry_after: Option<Duration> },
+    /// API キーが無効 (401 / 403)
+    Auth,
+    /// 言語コードが不正など、リクエスト自体の誤り (400)
+    InvalidRequest(String),
+    /// 通信エラーやサーバー側のエラーなど、時間を置けば成功しうるもの
+    Transient(String),
+}
+
+impl TranslateError {
+    /// 再試行する価値があるか
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            TranslateError::RateLimited { .. } | TranslateError::Transient(_)
+        )
+    }
+
+    /// コマンドのエラーに変換する
+    ///
+    /// 利用者が待つか入力を直すしかないものはそのまま伝え、それ以外は内部エラーとして扱う。
+    pub fn into_command_error(self) -> crate::Error {
+        match self {
+            TranslateError::QuotaExceeded
+            | TranslateError::RateLimited { .. }
+            | TranslateError::InvalidRequest(_) => {
+                Box::new(UserError::new(self.to_string(), self.message_en()))
+            }
+            _ => Box::new(self),
+        }
+    }
+
+    /// 利用者に見せる英語の文言
+    fn message_en(&self) -> String {
+        match self {
+            TranslateError::QuotaExceeded => {
+                "The translation character limit has been reached".to_string()
+            }
+            TranslateError::RateLimited { .. } => {
+                "Too many requests to the translation service".to_string()
+            }
+            TranslateError::Auth => {
+                "Authentication with the translation service failed".to_string()
+            }
+            TranslateError::InvalidRequest(detail) => {
+                format!("The translation request was invalid ({})", detail)
+            }
+            TranslateError::Transient(detail) => {
+                format!("Could not reach the translation service ({})", detail)
+            }
+        }
+    }
+
+    /// HTTP ステータスから分類する (成功時は None)
+    pub fn from_response(response: &reqwest::Response) -> Option<Self> {
+        let status = response.status();
+        if status.is_success() {
+            return None;
+        }
+        let error = match status.as_u16() {
+            456 => TranslateError::QuotaExceeded,
+            429 => TranslateError::RateLimited {
+                retry_after: response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok())
+                    .map(Duration::from_secs),
+            },
+            401 | 403 => TranslateError::Auth,
+            400 | 404 | 413 | 414 => TranslateError::InvalidRequest(status.to_string()),
+            _ => TranslateError::Transient(status.to_string()),
+        };
+        Some(error)
+    }
+}
+
+impl fmt::Display for TranslateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranslateError::QuotaExceeded => write!(f, "翻訳できる文字数の上限に達しました"),
+            TranslateError::RateLimited { .. } => {
+                write!(f, "翻訳サービスへのリクエストが多すぎます")
+            }
+            TranslateError::Auth => write!(f, "翻訳サービスの認証に失敗しました"),
+            TranslateError::InvalidRequest(detail) => {
+                write!(f, "翻訳リクエストが不正です ({})", detail)
+            }
+            TranslateError::Transient(detail) => {
+                write!(f, "翻訳サービスに接続できませんでした ({})", detail)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TranslateError {}
+
+impl From<reqwest::Error> for TranslateError {
+    fn from(err: reqwest::Error) -> Self {
+        TranslateError::Transient(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, retry_after: Option<&str>) -> reqwest::Response {
+        let mut builder = hyper::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            builder = builder.header(reqwest::header::RETRY_AFTER, retry_after);
+        }
+        reqwest::Response::from(builder.body(String::new()).unwrap())
+    }
+
+    #[test]
+    fn success_is_not_an_error() {
+        assert!(TranslateError::from_response(&response(200, None)).is_none());
+    }
+
+    #[test]
+    fn statuses_are_classified() {
+        let classify = |status| TranslateError::from_response(&response(status, None)).unwrap();
+        assert!(matches!(classify(456), TranslateError::QuotaExceeded));
+        assert!(matches!(classify(401), TranslateError::Auth));
+        assert!(matches!(classify(403), TranslateError::Auth));
+        assert!(matches!(classify(400), TranslateError::InvalidRequest(_)));
+        assert!(matches!(classify(413), TranslateError::InvalidRequest(_)));
+        assert!(matches!(classify(500), TranslateError::Transient(_)));
+        assert!(matches!(classify(503), TranslateError::Transient(_)));
+    }
+
+    #[test]
+    fn rate_limit_reads_retry_after() {
+        let error = TranslateError::from_response(&response(429, Some(" 7 "))).unwrap();
+        assert!(matches!(
+            error,
+            TranslateError::RateLimited {
+                retry_after: Some(duration)
+            } if duration == Duration::from_secs(7)
+        ));
+        assert!(error.is_retryable());
+
+        let error = TranslateError::from_response(&response(429, Some("soon"))).unwrap();
+        assert!(matches!(
+            error,
+            TranslateError::RateLimited { retry_after: None }
+        ));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        assert!(TranslateError::Transient(String::new()).is_retryable());
+        assert!(!TranslateError::QuotaExceeded.is_retryable());
+        assert!(!TranslateError::Auth.is_retryable());
+    }
+}
@@ -0,0 +1,79 @@
+/// 国旗の国コードと DeepL の target_lang の対応
+const FLAG_LANGUAGES: &[(&str, &str)] = &[
+    ("JP", "JA"),
+    ("US", "EN-US"),
+    ("GB", "EN-GB"),
+    ("AU", "EN-GB"),
+    ("CA", "EN-US"),
+    ("DE", "DE"),
+    ("AT", "DE"),
+    ("FR", "FR"),
+    ("ES", "ES"),
+    ("MX", "ES"),
+    ("IT", "IT"),
+    ("NL", "NL"),
+    ("PL", "PL"),
+    ("PT", "PT-PT"),
+    ("BR", "PT-BR"),
+    ("RU", "RU"),
+    ("UA", "UK"),
+    ("CN", "ZH-HANS"),
+    ("TW", "ZH-HANT"),
+    ("KR", "KO"),
+    ("ID", "ID"),
+    ("TR", "TR"),
+    ("SE", "SV"),
+    ("DK", "DA"),
+    ("FI", "FI"),
+    ("NO", "NB"),
+    ("GR", "EL"),
+    ("CZ", "CS"),
+    ("SK", "SK"),
+    ("SI", "SL"),
+    ("HU", "HU"),
+    ("RO", "RO"),
+    ("BG", "BG"),
+    ("EE", "ET"),
+    ("LV", "LV"),
+    ("LT", "LT"),
+    ("SA", "AR"),
+];
+
+/// 国旗の絵文字 (🇯🇵 など) を DeepL の target_lang に変換する
+///
+/// 国旗は2つの地域指示記号 (U+1F1E6〜U+1F1FF) の組み合わせで表される。
+pub fn target_lang_for_flag(emoji: &str) -> Option<&'static str> {
+    let mut code = String::new();
+    for c in emoji.chars() {
+        let offset = u32::from(c).checked_sub(0x1F1E6)?;
+        if offset >= 26 {
+            return None;
+        }
+        code.push(char::from(b'A' + offset as u8));
+    }
+    FLAG_LANGUAGES
+        .iter()
+        .find(|(country, _)| *country == code)
+        .map(|(_, lang)| *lang)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_flags_map_to_languages() {
+        assert_eq!(target_lang_for_flag("🇯🇵"), Some("JA"));
+        assert_eq!(target_lang_for_flag("🇺🇸"), Some("EN-US"));
+        assert_eq!(target_lang_for_flag("🇧🇷"), Some("PT-BR"));
+    }
+
+    #[test]
+    fn other_emoji_are_ignored() {
+        // 対応する言語が無い国旗
+        assert_eq!(target_lang_for_flag("🇦🇶"), None);
+        assert_eq!(target_lang_for_flag("👍"), None);
+        assert_eq!(target_lang_for_flag("JP"), None);
+        assert_eq!(target_lang_for_flag(""), None);
+    }
+}
@@ -0,0 +1,123 @@
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// 翻訳先として指定できる言語
+#[derive(Deserialize, Debug, Clone)]
+pub struct Language {
+    /// DeepL の言語コード (例: "EN-US")
+    #[serde(rename = "language")]
+    pub code: String,
+    pub name: String,
+    #[serde(default)]
+    pub supports_formality: bool,
+}
+
+/// 取得した言語一覧を使い回す時間
+const LANGUAGES_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 組み込みの一覧で代用したときに取得し直すまでの時間
+const FALLBACK_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// サービスから取得できない場合の言語一覧 (DeepL の翻訳先言語)
+const FALLBACK_LANGUAGES: &[(&str, &str, bool)] = &[
+    ("AR", "Arabic", false),
+    ("BG", "Bulgarian", false),
+    ("CS", "Czech", false),
+    ("DA", "Danish", false),
+    ("DE", "German", true),
+    ("EL", "Greek", false),
+    ("EN-GB", "English (British)", false),
+    ("EN-US", "English (American)", false),
+    ("ES", "Spanish", true),
+    ("ET", "Estonian", false),
+    ("FI", "Finnish", false),
+    ("FR", "French", true),
+    ("HU", "Hungarian", false),
+    ("ID", "Indonesian", false),
+    ("IT", "Italian", true),
+    ("JA", "Japanese", true),
+    ("KO", "Korean", false),
+    ("LT", "Lithuanian", false),
+    ("LV", "Latvian", false),
+    ("NB", "Norwegian (Bokmål)", false),
+    ("NL", "Dutch", true),
+    ("PL", "Polish", true),
+    ("PT-BR", "Portuguese (Brazilian)", true),
+    ("PT-PT", "Portuguese (European)", true),
+    ("RO", "Romanian", false),
+    ("RU", "Russian", true),
+    ("SK", "Slovak", false),
+    ("SL", "Slovenian", false),
+    ("SV", "Swedish", false),
+    ("TR", "Turkish", false),
+    ("UK", "Ukrainian", false),
+    ("ZH-HANS", "Chinese (simplified)", false),
+    ("ZH-HANT", "Chinese (traditional)", false),
+];
+
+/// 非推奨だが DeepL が受け付ける言語コードの読み替え
+const ALIASES: &[(&str, &str)] = &[("EN", "EN-US"), ("PT", "PT-PT"), ("ZH", "ZH-HANS")];
+
+/// 期限と言語一覧
+type CachedLanguages = (Instant, Arc<Vec<Language>>);
+
+/// バックエンド名ごとの言語一覧
+static LANGUAGES: Lazy<RwLock<HashMap<&'static str, CachedLanguages>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn fallback_languages() -> Vec<Language> {
+    FALLBACK_LANGUAGES
+        .iter()
+        .map(|(code, name, supports_formality)| Language {
+            code: code.to_string(),
+            name: name.to_string(),
+            supports_formality: *supports_formality,
+        })
+        .collect()
+}
+
+/// 現在のバックエンドで使える翻訳先言語の一覧
+///
+/// 取得結果はバックエンドごとに一定時間キャッシュし、取得できない場合は組み込みの一覧を返す。
+/// 組み込みの一覧は短い時間で取得し直す。
+pub async fn target_languages() -> Arc<Vec<Language>> {
+    let backend = super::current_backend();
+    if let Some((expires_at, languages)) = LANGUAGES.read().unwrap().get(backend.name()) {
+        if Instant::now() < *expires_at {
+            return Arc::clone(languages);
+        }
+    }
+    let (languages, ttl) = match backend.target_languages().await {
+        Ok(Some(languages)) if !languages.is_empty() => (languages, LANGUAGES_TTL),
+        Ok(_) => (fallback_languages(), FALLBACK_TTL),
+        Err(err) => {
+            tracing::warn!(
+                "{} の言語一覧を取得できませんでした: {}",
+                backend.name(),
+                err
+            );
+            (fallback_languages(), FALLBACK_TTL)
+        }
+    };
+    let languages = Arc::new(languages);
+    LANGUAGES.write().unwrap().insert(
+        backend.name(),
+        (Instant::now() + ttl, Arc::clone(&languages)),
+    );
+    languages
+}
+
+/// 入力された言語コードを一覧の言語に解決する
+pub fn find_language<'a>(languages: &'a [Language], code: &str) -> Option<&'a Language> {
+    let code = code.trim().to_uppercase();
+    let code = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == code)
+        .map_or(code.as_str(), |(_, canonical)| canonical);
+    languages
+        .iter()
+        .find(|language| language.code.eq_ignore_ascii_case(code))
+}
@@ -0,0 +1,83 @@
+use poise::serenity_prelude::async_trait;
+use serde::Deserialize;
+
+use super::{TranslateError, TranslateOptions, Translation, TranslationBackend};
+use crate::config::LibreTranslateSettings;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TranslateResponse {
+    translated_text: String,
+    detected_language: Option<DetectedLanguage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DetectedLanguage {
+    language: String,
+}
+
+/// セルフホストの LibreTranslate
+pub struct LibreTranslateBackend {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl LibreTranslateBackend {
+    pub fn new(client: reqwest::Client, settings: &LibreTranslateSettings) -> Self {
+        Self {
+            client,
+            url: settings.url.trim_end_matches('/').to_string(),
+            api_key: settings.api_key.clone(),
+        }
+    }
+}
+
+/// DeepL 形式の言語コード ("EN-US" など) を LibreTranslate 形式 ("en") にする
+fn to_libre_lang(target_lang: &str) -> String {
+    let lang = target_lang.to_lowercase();
+    match lang.as_str() {
+        "zh-hant" => "zt".to_string(),
+        "pt-br" | "pt-pt" => "pt".to_string(),
+        _ => lang.split('-').next().unwrap_or_default().to_string(),
+    }
+}
+
+#[async_trait]
+impl TranslationBackend for LibreTranslateBackend {
+    fn name(&self) -> &'static str {
+        "LibreTranslate"
+    }
+
+    async fn translate(
+        &self,
+        text: &str,
+        target_lang: &str,
+        options: &TranslateOptions<'_>,
+    ) -> Result<Translation, TranslateError> {
+        let response = self
+            .client
+            .post(format!("{}/translate", self.url))
+            .json(&serde_json::json!({
+                "q": text,
+                "source": options.source_lang.map_or("auto".to_string(), to_libre_lang),
+                "target": to_libre_lang(target_lang),
+                "format": "html",
+                "api_key": self.api_key,
+            }))
+            .send()
+            .await?;
+        if let Some(err) = TranslateError::from_response(&response) {
+            return Err(err);
+        }
+        let response = response.json::<TranslateResponse>().await?;
+
+        Ok(Translation {
+            detected_source_language: response
+                .detected_language
+                .map(|detected| detected.language.to_uppercase())
+                .unwrap_or_default(),
+            text: response.translated_text.trim().to_string(),
+        })
+    }
+}
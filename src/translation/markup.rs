@@ -0,0 +1,230 @@
+/// 翻訳前に保護した Discord の書式
+///
+/// メンション、チャンネルリンク、カスタム絵文字、コード、URL などは `<x id="N"/>` に置き換え、
+/// スポイラーは中身を翻訳できるよう `<s>…</s>` で囲む。それ以外の文字は XML としてエスケープする。
+pub struct ProtectedText {
+    /// 翻訳サービスに送る XML 形式の文字列
+    pub markup: String,
+    spans: Vec<String>,
+}
+
+/// `<...>` 形式の Discord のトークン (メンション、絵文字、タイムスタンプ、URL など) か
+fn is_discord_token(inner: &str) -> bool {
+    !inner.is_empty()
+        && !inner.contains(char::is_whitespace)
+        && (inner.starts_with(['@', '#', ':', '/'])
+            || inner.starts_with("a:")
+            || inner.starts_with("t:")
+            || inner.starts_with("http://")
+            || inner.starts_with("https://"))
+}
+
+/// `text` の先頭から保護すべき範囲の長さ (バイト数) を返す
+fn protected_len(text: &str) -> Option<usize> {
+    if let Some(rest) = text.strip_prefix("```") {
+        return rest.find("```").map(|end| end + 6);
+    }
+    if let Some(rest) = text.strip_prefix('`') {
+        return rest.find('`').map(|end| end + 2);
+    }
+    if let Some(rest) = text.strip_prefix('<') {
+        let end = rest.find('>')?;
+        return is_discord_token(&rest[..end]).then_some(end + 2);
+    }
+    if text.starts_with("http://") || text.starts_with("https://") {
+        return Some(text.find(char::is_whitespace).unwrap_or(text.len()));
+    }
+    ["@everyone", "@here"]
+        .into_iter()
+        .find(|mention| text.starts_with(mention))
+        .map(str::len)
+}
+
+fn escape_char(c: char, out: &mut String) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(c),
+    }
+}
+
+/// 翻訳サービスに送る形に変換する
+pub fn protect(text: &str) -> ProtectedText {
+    let mut markup = String::with_capacity(text.len());
+    let mut spans = Vec::new();
+    // 開いているスポイラーの閉じ位置
+    let mut spoiler_end: Option<usize> = None;
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if let Some(inner) = rest.strip_prefix("||") {
+            if spoiler_end == Some(i) {
+                markup.push_str("</s>");
+                spoiler_end = None;
+                i += 2;
+                continue;
+            }
+            if spoiler_end.is_none() {
+                if let Some(end) = inner.find("||") {
+                    markup.push_str("<s>");
+                    spoiler_end = Some(i + 2 + end);
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+        if let Some(len) = protected_len(rest) {
+            // スポイラーの閉じをまたぐ場合は保護しない (タグの対応が崩れるため)
+            if spoiler_end.is_none_or(|end| i + len <= end) {
+                markup.push_str(&format!("<x id=\"{}\"/>", spans.len()));
+                spans.push(rest[..len].to_string());
+                i += len;
+                continue;
+            }
+        }
+        let c = rest.chars().next().unwrap();
+        escape_char(c, &mut markup);
+        i += c.len_utf8();
+    }
+    if spoiler_end.is_some() {
+        markup.push_str("</s>");
+    }
+    ProtectedText { markup, spans }
+}
+
+/// `<x id="N"/>` (空白の揺れや `<x id="N"></x>` も許容) なら (N, 長さ) を返す
+fn parse_placeholder(text: &str) -> Option<(usize, usize)> {
+    let rest = text.strip_prefix("<x")?;
+    let end = rest.find('>')?;
+    let tag = &rest[..end];
+    let id = tag
+        .trim()
+        .trim_end_matches('/')
+        .trim()
+        .strip_prefix("id=")?
+        .trim_matches(['"', '\'']);
+    let id: usize = id.parse().ok()?;
+    let mut len = 2 + end + 1;
+    if !tag.trim_end().ends_with('/') {
+        len += text[len..].starts_with("</x>").then_some(4)?;
+    }
+    Some((id, len))
+}
+
+const ENTITIES: &[(&str, char)] = &[
+    ("&amp;", '&'),
+    ("&lt;", '<'),
+    ("&gt;", '>'),
+    ("&quot;", '"'),
+    ("&apos;", '\''),
+    ("&#39;", '\''),
+];
+
+impl ProtectedText {
+    /// 翻訳結果の置き換えを元に戻す
+    ///
+    /// 翻訳サービスが落とした保護範囲は、失われないよう末尾に付け足す。
+    pub fn restore(&self, translated: &str) -> String {
+        let mut out = String::with_capacity(translated.len());
+        let mut used = vec![false; self.spans.len()];
+        let mut i = 0;
+        'outer: while i < translated.len() {
+            let rest = &translated[i..];
+            if let Some((id, len)) = parse_placeholder(rest) {
+                if let Some(span) = self.spans.get(id) {
+                    out.push_str(span);
+                    used[id] = true;
+                    i += len;
+                    continue;
+                }
+            }
+            for tag in ["<s>", "</s>"] {
+                if rest.starts_with(tag) {
+                    out.push_str("||");
+                    i += tag.len();
+                    continue 'outer;
+                }
+            }
+            for (entity, c) in ENTITIES {
+                if rest.starts_with(entity) {
+                    out.push(*c);
+                    i += entity.len();
+                    continue 'outer;
+                }
+            }
+            let c = rest.chars().next().unwrap();
+            out.push(c);
+            i += c.len_utf8();
+        }
+        for (span, used) in self.spans.iter().zip(used) {
+            if !used {
+                out.push(' ');
+                out.push_str(span);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 翻訳サービスがそのまま返した場合に元の文字列へ戻ること
+    fn assert_round_trip(text: &str) {
+        assert_eq!(protect(text).restore(&protect(text).markup), text);
+    }
+
+    #[test]
+    fn code_is_protected() {
+        let text = "run ```let a = 1;\nlet b = 2;``` and `cargo test`";
+        let protected = protect(text);
+        assert_eq!(protected.markup, "run <x id=\"0\"/> and <x id=\"1\"/>");
+        assert_round_trip(text);
+    }
+
+    #[test]
+    fn mentions_emoji_and_urls_are_protected() {
+        let text = "<@123> <#456> <:smile:789> <a:wave:1> @everyone https://example.com/a?b=c";
+        let protected = protect(text);
+        assert!(!protected.markup.contains("123"));
+        assert!(!protected.markup.contains("example.com"));
+        assert_eq!(protected.spans.len(), 6);
+        assert_round_trip(text);
+    }
+
+    #[test]
+    fn xml_characters_are_escaped() {
+        let text = "a < b && \"c\" > d";
+        assert_eq!(
+            protect(text).markup,
+            "a &lt; b &amp;&amp; &quot;c&quot; &gt; d"
+        );
+        assert_round_trip(text);
+    }
+
+    #[test]
+    fn spoilers_keep_their_content_translatable() {
+        let text = "||secret `code`||";
+        let protected = protect(text);
+        assert_eq!(protected.markup, "<s>secret <x id=\"0\"/></s>");
+        assert_round_trip(text);
+    }
+
+    #[test]
+    fn reordered_and_reformatted_placeholders_are_restored() {
+        let protected = protect("<@1> likes `x`");
+        assert_eq!(
+            protected.restore("<x id=\"1\"></x> は <x id='0' /> が好き"),
+            "`x` は <@1> が好き"
+        );
+    }
+
+    #[test]
+    fn dropped_spans_are_appended() {
+        let protected = protect("see https://example.com");
+        assert_eq!(protected.restore("見て"), "見て https://example.com");
+    }
+}
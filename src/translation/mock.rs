@@ -0,0 +1,129 @@
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::async_trait;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{TranslateError, TranslateOptions, Translation, TranslationBackend};
+
+/// モックで作成した用語集 (ID → 用語と訳語)
+static GLOSSARIES: Lazy<DashMap<String, BTreeMap<String, String>>> = Lazy::new(DashMap::new);
+static NEXT_GLOSSARY_ID: AtomicU64 = AtomicU64::new(1);
+
+/// かなや漢字を含めば日本語とみなす簡易的な言語判定
+fn detect_language(text: &str) -> &'static str {
+    let japanese = text
+        .chars()
+        .any(|c| matches!(c, '\u{3040}'..='\u{30ff}' | '\u{4e00}'..='\u{9fff}'));
+    if japanese {
+        "JA"
+    } else {
+        "EN"
+    }
+}
+
+/// 外部に接続しない翻訳バックエンド (オフラインでの動作確認用)
+///
+/// 原文をそのまま `[言語コード] 原文` の形で返す。用語集が指定されていれば、
+/// 原文中の用語を訳語に置き換える。原文の言語が指定されていなければ、
+/// かなや漢字を含むものを JA、それ以外を EN として検出したことにする。
+pub struct MockBackend;
+
+#[async_trait]
+impl TranslationBackend for MockBackend {
+    fn name(&self) -> &'static str {
+        "Mock"
+    }
+
+    async fn translate(
+        &self,
+        text: &str,
+        target_lang: &str,
+        options: &TranslateOptions<'_>,
+    ) -> Result<Translation, TranslateError> {
+        let detected_source_language = match options.source_lang {
+            Some(source_lang) => source_lang.to_uppercase(),
+            None => detect_language(text).to_string(),
+        };
+        let mut text = text.to_string();
+        // 再起動で消えた用語集は無視する
+        if let Some(entries) = options.glossary_id.and_then(|id| GLOSSARIES.get(id)) {
+            for (term, translation) in entries.iter() {
+                text = text.replace(term, translation);
+            }
+        }
+        Ok(Translation {
+            detected_source_language,
+            text: format!("[{}] {}", target_lang.to_uppercase(), text),
+        })
+    }
+
+    async fn create_glossary(
+        &self,
+        _name: &str,
+        _source_lang: &str,
+        _target_lang: &str,
+        entries: &BTreeMap<String, String>,
+    ) -> Result<Option<String>, TranslateError> {
+        let glossary_id = format!("mock-{}", NEXT_GLOSSARY_ID.fetch_add(1, Ordering::Relaxed));
+        GLOSSARIES.insert(glossary_id.clone(), entries.clone());
+        Ok(Some(glossary_id))
+    }
+
+    async fn delete_glossary(&self, glossary_id: &str) -> Result<(), TranslateError> {
+        GLOSSARIES.remove(glossary_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn detects_language_when_source_is_unset() {
+        let options = TranslateOptions::default();
+        let translation = MockBackend
+            .translate("こんにちは", "EN-US", &options)
+            .await
+            .unwrap();
+        assert_eq!(translation.detected_source_language, "JA");
+        assert_eq!(translation.text, "[EN-US] こんにちは");
+
+        let translation = MockBackend
+            .translate("hello", "ja", &options)
+            .await
+            .unwrap();
+        assert_eq!(translation.detected_source_language, "EN");
+        assert_eq!(translation.text, "[JA] hello");
+    }
+
+    #[tokio::test]
+    async fn glossary_round_trip() {
+        let entries = BTreeMap::from([("猫".to_string(), "cat".to_string())]);
+        let glossary_id = MockBackend
+            .create_glossary("test", "JA", "EN", &entries)
+            .await
+            .unwrap()
+            .unwrap();
+        let options = TranslateOptions {
+            source_lang: Some("ja"),
+            glossary_id: Some(&glossary_id),
+            ..Default::default()
+        };
+        let translation = MockBackend
+            .translate("猫が好き", "EN", &options)
+            .await
+            .unwrap();
+        assert_eq!(translation.detected_source_language, "JA");
+        assert_eq!(translation.text, "[EN] catが好き");
+
+        // 削除した用語集は適用されない
+        MockBackend.delete_glossary(&glossary_id).await.unwrap();
+        let translation = MockBackend
+            .translate("猫が好き", "EN", &options)
+            .await
+            .unwrap();
+        assert_eq!(translation.text, "[EN] 猫が好き");
+    }
+}
@@ -0,0 +1,198 @@
+mod cache;
+mod deepl;
+mod error;
+mod flags;
+mod languages;
+mod libre;
+mod markup;
+mod mock;
+mod usage;
+
+pub use cache::{CacheKey, TranslationCache};
+pub use deepl::DeeplBackend;
+pub use error::TranslateError;
+pub use flags::target_lang_for_flag;
+pub use languages::{find_language, target_languages, Language};
+pub use libre::LibreTranslateBackend;
+pub use markup::protect;
+pub use mock::MockBackend;
+pub use usage::{check_usage, spawn_usage_monitor, usage_tracker, Origin, Usage};
+
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{self, BackendKind};
+
+/// 翻訳結果
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Translation {
+    /// 検出された原文の言語 (例: "JA")
+    pub detected_source_language: String,
+    pub text: String,
+}
+
+/// 翻訳時の追加オプション
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TranslateOptions<'a> {
+    /// 原文の言語 (None なら自動検出)。用語集を使う場合は必須
+    pub source_lang: Option<&'a str>,
+    /// 適用する用語集の ID
+    pub glossary_id: Option<&'a str>,
+    /// 文体 (DeepL の formality。例: "prefer_more")
+    pub formality: Option<&'a str>,
+}
+
+/// 翻訳先の言語に使える用語集
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlossaryChoice<'a> {
+    /// 用語集の原文の言語 (例: "JA")
+    pub source_lang: &'a str,
+    pub glossary_id: &'a str,
+}
+
+impl<'a> TranslateOptions<'a> {
+    /// 用語集と、その原文の言語を指定したオプション
+    pub fn with_glossary(self, glossary: GlossaryChoice<'a>) -> Self {
+        Self {
+            source_lang: Some(glossary.source_lang),
+            glossary_id: Some(glossary.glossary_id),
+            ..self
+        }
+    }
+}
+
+/// 翻訳サービスの共通インターフェース
+#[async_trait]
+pub trait TranslationBackend: Send + Sync {
+    /// ログなどに表示する名前
+    fn name(&self) -> &'static str;
+
+    /// `text` を `target_lang` (DeepL 形式の言語コード) に翻訳する
+    ///
+    /// `text` は [`protect`] で変換した XML 形式で、結果も同じ形式で返す。
+    async fn translate(
+        &self,
+        text: &str,
+        target_lang: &str,
+        options: &TranslateOptions<'_>,
+    ) -> Result<Translation, TranslateError>;
+
+    /// 今期の使用量 (取得できないサービスでは None)
+    async fn usage(&self) -> Result<Option<Usage>, TranslateError> {
+        Ok(None)
+    }
+
+    /// 翻訳先として使える言語 (一覧を提供しないサービスでは None)
+    async fn target_languages(&self) -> Result<Option<Vec<Language>>, TranslateError> {
+        Ok(None)
+    }
+
+    /// 用語集を作成して ID を返す (用語集に対応しないサービスでは None)
+    async fn create_glossary(
+        &self,
+        _name: &str,
+        _source_lang: &str,
+        _target_lang: &str,
+        _entries: &BTreeMap<String, String>,
+    ) -> Result<Option<String>, TranslateError> {
+        Ok(None)
+    }
+
+    /// 用語集を削除する
+    async fn delete_glossary(&self, _glossary_id: &str) -> Result<(), TranslateError> {
+        Ok(())
+    }
+}
+
+/// 各バックエンドで共有する HTTP クライアント
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+/// 地域の違い (EN-US と EN-GB など) を無視して同じ言語か判定する
+pub fn same_language(detected: &str, target_lang: &str) -> bool {
+    let base = |lang: &str| lang.split('-').next().unwrap_or("").to_uppercase();
+    base(detected) == base(target_lang)
+}
+
+/// Discord の表示言語 (例: "ja", "en-US", "zh-TW") を DeepL の target_lang に変換する
+pub fn target_lang_for_locale(locale: &str) -> String {
+    match locale {
+        "en-US" | "en-GB" | "pt-BR" => locale.to_uppercase(),
+        "zh-CN" => "ZH-HANS".to_string(),
+        "zh-TW" => "ZH-HANT".to_string(),
+        _ => locale.split('-').next().unwrap_or(locale).to_uppercase(),
+    }
+}
+
+/// 翻訳結果のキャッシュ (設定は初回利用時のものを使う)
+static CACHE: Lazy<TranslationCache> =
+    Lazy::new(|| TranslationCache::new(&config::global().translation.cache));
+
+pub fn cache() -> &'static TranslationCache {
+    &CACHE
+}
+
+/// 現在の設定に応じたバックエンドを返す
+///
+/// 設定が再読み込みされても反映されるよう、呼び出しごとに生成する。
+pub fn current_backend() -> Arc<dyn TranslationBackend> {
+    let settings = config::global();
+    match settings.translation.backend {
+        BackendKind::Deepl => Arc::new(DeeplBackend::from_settings(HTTP_CLIENT.clone(), &settings)),
+        BackendKind::LibreTranslate => Arc::new(LibreTranslateBackend::new(
+            HTTP_CLIENT.clone(),
+            &settings.translation.libretranslate,
+        )),
+        BackendKind::Mock => Arc::new(MockBackend),
+    }
+}
+
+/// 再試行の最大回数 (初回を除く)
+const MAX_RETRIES: u32 = 3;
+/// 再試行の待ち時間の初期値 (毎回2倍にする)
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Retry-After がこれより長い場合は待たずに諦める
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// 一時的な失敗であれば指数バックオフで再試行しながら翻訳する
+pub async fn translate_with_retry(
+    backend: &dyn TranslationBackend,
+    text: &str,
+    target_lang: &str,
+    options: &TranslateOptions<'_>,
+) -> Result<Translation, TranslateError> {
+    let mut delay = INITIAL_RETRY_DELAY;
+    let mut attempt = 0;
+    loop {
+        let err = match backend.translate(text, target_lang, options).await {
+            Ok(translation) => return Ok(translation),
+            Err(err) => err,
+        };
+        if !err.is_retryable() || attempt >= MAX_RETRIES {
+            return Err(err);
+        }
+        let wait = match &err {
+            TranslateError::RateLimited {
+                retry_after: Some(retry_after),
+            } => *retry_after,
+            _ => delay,
+        };
+        if wait > MAX_RETRY_DELAY {
+            return Err(err);
+        }
+        tracing::warn!(
+            "{} での翻訳に失敗しました。{}ms 後に再試行します ({}/{}): {}",
+            backend.name(),
+            wait.as_millis(),
+            attempt + 1,
+            MAX_RETRIES,
+            err
+        );
+        tokio::time::sleep(wait).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
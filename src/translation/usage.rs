@@ -0,0 +1,279 @@
+use chrono::{DateTime, Local};
+use poise::serenity_prelude::{ChannelId, Color, CreateEmbed, CreateMessage, GuildId, Http};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::config;
+
+/// 文字数の集計の保存先
+const USAGE_PATH: &str = "data/translation_usage.json";
+
+/// 翻訳サービス側の使用量
+#[derive(Debug, Clone, Copy)]
+pub struct Usage {
+    pub character_count: u64,
+    pub character_limit: u64,
+}
+
+impl Usage {
+    /// 使用率 (%)。上限が無い場合は None
+    pub fn percent(&self) -> Option<f64> {
+        (self.character_limit > 0)
+            .then(|| self.character_count as f64 * 100.0 / self.character_limit as f64)
+    }
+}
+
+/// 翻訳を依頼した場所 (文字数の集計に使う)
+#[derive(Debug, Clone, Copy)]
+pub struct Origin {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct ChannelUsage {
+    guild_id: Option<u64>,
+    characters: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct UsageData {
+    /// 集計中の月 ("2025-06" の形式)。月が変わったら集計をやり直す
+    month: String,
+    guilds: HashMap<u64, u64>,
+    channels: HashMap<u64, ChannelUsage>,
+    /// 警告済みのしきい値
+    warned: BTreeSet<u8>,
+}
+
+/// 翻訳した文字数のローカル集計と、DeepL の使用量の監視
+///
+/// 集計はメモリ上で行い、使用量の確認のたびと停止時に [`UsageTracker::flush`] でファイルに書き出す。
+pub struct UsageTracker {
+    path: PathBuf,
+    data: Mutex<UsageData>,
+    /// ファイルに書き出していない変更があるか
+    dirty: AtomicBool,
+    /// 書き込みを直列化するためのロック (定期保存と停止時の保存が重なることがある)
+    write_lock: Mutex<()>,
+    /// 最後に取得した翻訳サービスの使用量
+    last_usage: Mutex<Option<(DateTime<Local>, Usage)>>,
+}
+
+fn current_month() -> String {
+    Local::now().format("%Y-%m").to_string()
+}
+
+impl UsageTracker {
+    fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let data = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            data: Mutex::new(data),
+            dirty: AtomicBool::new(false),
+            write_lock: Mutex::new(()),
+            last_usage: Mutex::new(None),
+        }
+    }
+
+    /// 変更があれば集計をファイルに書き出す
+    pub fn flush(&self) {
+        // 後から書き出す側が必ず新しい内容を書くよう、変更の確認から書き込みまでを直列化する
+        let _guard = self.write_lock.lock().unwrap();
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        // 書き込み中に翻訳を待たせないよう、ロックは文字列にするまでにとどめる
+        let json = match serde_json::to_string(&*self.data.lock().unwrap()) {
+            Ok(json) => json,
+            Err(err) => {
+                tracing::warn!("翻訳文字数を保存できる形式にできませんでした: {:?}", err);
+                return;
+            }
+        };
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            // 書き込み途中で落ちても壊れないよう、一時ファイルに書いてから置き換える
+            let tmp_path = self.path.with_extension("json.tmp");
+            std::fs::write(&tmp_path, json)?;
+            std::fs::rename(&tmp_path, &self.path)
+        })();
+        if let Err(err) = result {
+            // 次の機会に書き出し直す
+            self.dirty.store(true, Ordering::SeqCst);
+            tracing::warn!(
+                "翻訳文字数の保存に失敗しました ({}): {:?}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+
+    /// 翻訳サービスに送った文字数を記録する
+    pub fn record(&self, origin: Origin, characters: usize) {
+        let characters = characters as u64;
+        let mut data = self.data.lock().unwrap();
+        let month = current_month();
+        if data.month != month {
+            data.month = month;
+            data.guilds.clear();
+            data.channels.clear();
+        }
+        if let Some(guild_id) = origin.guild_id {
+            *data.guilds.entry(guild_id.get()).or_default() += characters;
+        }
+        let channel = data.channels.entry(origin.channel_id.get()).or_default();
+        channel.guild_id = origin.guild_id.map(|guild_id| guild_id.get());
+        channel.characters += characters;
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    /// 今月ギルドで翻訳した文字数
+    pub fn guild_characters(&self, guild_id: GuildId) -> u64 {
+        let data = self.data.lock().unwrap();
+        if data.month != current_month() {
+            return 0;
+        }
+        data.guilds.get(&guild_id.get()).copied().unwrap_or(0)
+    }
+
+    /// 今月の文字数が多いチャンネル (guild_id を指定するとそのギルドに絞る)
+    pub fn top_channels(&self, guild_id: Option<GuildId>, limit: usize) -> Vec<(ChannelId, u64)> {
+        let data = self.data.lock().unwrap();
+        if data.month != current_month() {
+            return Vec::new();
+        }
+        let mut channels: Vec<(ChannelId, u64)> = data
+            .channels
+            .iter()
+            .filter(|(_, usage)| {
+                guild_id.is_none_or(|guild_id| usage.guild_id == Some(guild_id.get()))
+            })
+            .map(|(channel_id, usage)| (ChannelId::new(*channel_id), usage.characters))
+            .collect();
+        channels.sort_by_key(|(_, characters)| std::cmp::Reverse(*characters));
+        channels.truncate(limit);
+        channels
+    }
+
+    /// 今月の文字数が多いギルド
+    pub fn top_guilds(&self, limit: usize) -> Vec<(GuildId, u64)> {
+        let data = self.data.lock().unwrap();
+        if data.month != current_month() {
+            return Vec::new();
+        }
+        let mut guilds: Vec<(GuildId, u64)> = data
+            .guilds
+            .iter()
+            .map(|(guild_id, characters)| (GuildId::new(*guild_id), *characters))
+            .collect();
+        guilds.sort_by_key(|(_, characters)| std::cmp::Reverse(*characters));
+        guilds.truncate(limit);
+        guilds
+    }
+
+    pub fn last_usage(&self) -> Option<(DateTime<Local>, Usage)> {
+        *self.last_usage.lock().unwrap()
+    }
+
+    /// 取得した使用量を記録し、新たに超えたしきい値のうち最大のものを返す
+    ///
+    /// 使用率がしきい値を下回った場合 (請求期間が変わった場合など) は再び警告できるようにする。
+    fn update_usage(&self, usage: Usage, thresholds: &[u8]) -> Option<u8> {
+        *self.last_usage.lock().unwrap() = Some((Local::now(), usage));
+        let percent = usage.percent()?;
+        let mut data = self.data.lock().unwrap();
+        let mut crossed = None;
+        let mut changed = false;
+        for &threshold in thresholds {
+            if percent >= f64::from(threshold) {
+                if data.warned.insert(threshold) {
+                    crossed = crossed.max(Some(threshold));
+                    changed = true;
+                }
+            } else if data.warned.remove(&threshold) {
+                changed = true;
+            }
+        }
+        if changed {
+            self.dirty.store(true, Ordering::SeqCst);
+        }
+        crossed
+    }
+}
+
+static USAGE: once_cell::sync::Lazy<UsageTracker> =
+    once_cell::sync::Lazy::new(|| UsageTracker::load(USAGE_PATH));
+
+pub fn usage_tracker() -> &'static UsageTracker {
+    &USAGE
+}
+
+/// 翻訳サービスの使用量を取得し、しきい値を超えていれば管理チャンネルに警告する
+pub async fn check_usage(http: &Http) -> Option<Usage> {
+    let backend = super::current_backend();
+    let usage = match backend.usage().await {
+        Ok(usage) => usage?,
+        Err(err) => {
+            tracing::warn!("{} の使用量を取得できませんでした: {}", backend.name(), err);
+            return None;
+        }
+    };
+    let settings = config::global().translation.usage.clone();
+    let crossed = usage_tracker().update_usage(usage, &settings.thresholds);
+    if let Some(threshold) = crossed {
+        tracing::warn!(
+            "{} の使用率が {}% を超えました ({}/{} 文字)",
+            backend.name(),
+            threshold,
+            usage.character_count,
+            usage.character_limit
+        );
+        if settings.admin_channel != 0 {
+            let embed = CreateEmbed::new()
+                .title("翻訳の使用量に関する警告")
+                .color(Color::ORANGE)
+                .description(format!(
+                    "{} の使用率が **{}%** を超えました。\n{} / {} 文字 ({:.1}%)",
+                    backend.name(),
+                    threshold,
+                    usage.character_count,
+                    usage.character_limit,
+                    usage.percent().unwrap_or(0.0)
+                ));
+            let builder = CreateMessage::new().add_embed(embed);
+            if let Err(err) = ChannelId::new(settings.admin_channel)
+                .send_message(http, builder)
+                .await
+            {
+                tracing::warn!("使用量の警告を送信できませんでした: {:?}", err);
+            }
+        }
+    }
+    Some(usage)
+}
+
+/// 一定間隔で使用量を確認し、文字数の集計をファイルに書き出すタスクを起動する
+pub fn spawn_usage_monitor(http: Arc<Http>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            check_usage(&http).await;
+            let _ = tokio::task::spawn_blocking(|| usage_tracker().flush()).await;
+            let interval = config::global().translation.usage.check_interval_secs;
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    })
+}
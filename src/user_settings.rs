@@ -0,0 +1,18 @@
+use poise::serenity_prelude::UserId;
+use serde::{Deserialize, Serialize};
+
+use crate::guild_settings::SettingsStore;
+
+/// ユーザー設定の保存先ディレクトリ (ユーザーごとに `<user_id>.json`)
+pub const USER_SETTINGS_DIR: &str = "data/users";
+
+/// ユーザー設定の永続化ストア
+pub type UserSettingsStore = SettingsStore<UserId, UserSettings>;
+
+/// ユーザーごとの設定
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct UserSettings {
+    /// 翻訳コマンドで使う翻訳先言語 (未設定なら Discord の表示言語)
+    pub translate_lang: Option<String>,
+}